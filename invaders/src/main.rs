@@ -1,15 +1,320 @@
-use ligma::game::LigmaInvaders;
-
-fn main() {
-    match LigmaInvaders::new() {
-        Ok(mut game) => match game.start() {
-            Ok(_) => (),
-            Err(err) => {
-                println!("{err}");
-                game.reset_screen()
-                    .expect("error while resetting the screen");
-            }
-        },
-        Err(err) => println!("{err}"),
+mod simulate;
+
+use std::{error::Error, path::PathBuf, process::ExitCode};
+
+use clap::{Args, Parser, Subcommand};
+use ligma::{
+    assets::Assets,
+    config::{Difficulty, GameConfig},
+    game::LigmaInvaders,
+    high_scores::HighScores,
+    key_bindings::KeyBindings,
+    ligma_result::{LigmaError, LigmaResult},
+    recording::Recording,
+    viewport::Viewport,
+};
+
+/// Space Invaders in the terminal.
+///
+/// Without a command the game is played, taking the same options as `invaders play`.
+#[derive(Debug, Parser)]
+#[command(name = "invaders", version, args_conflicts_with_subcommands = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    #[command(flatten)]
+    play: PlayArgs,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Play the game.
+    Play(PlayArgs),
+    /// Watch a game recorded with `play --record`.
+    Replay(ReplayArgs),
+    /// Play games without a terminal and print how they ended.
+    Simulate(SimulateArgs),
+    /// Check the asset files in a directory, reporting the first error.
+    ValidateAssets {
+        /// Directory with asset overrides. Files missing from it are taken from the embedded set.
+        dir: PathBuf,
+    },
+    /// Print version and build information.
+    Version,
+}
+
+/// Options deciding how a game plays out.
+#[derive(Debug, Args)]
+struct GameArgs {
+    /// Seed for every game, random when not given.
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Difficulty preset: easy, normal, hard or arcade.
+    #[arg(long, value_parser = parse_difficulty)]
+    difficulty: Option<Difficulty>,
+    /// Game config file. Defaults to `config.toml` in the user's config directory.
+    #[arg(long, value_name = "FILE")]
+    config: Option<PathBuf>,
+    /// Overrides a single config field, e.g. `--set aliens.wave_speedup=20`. May be repeated.
+    #[arg(long = "set", value_name = "FIELD=VALUE", value_parser = parse_override)]
+    overrides: Vec<(String, String)>,
+    /// Score at which an extra life is granted, 0 to never grant one.
+    #[arg(long, value_name = "SCORE")]
+    extra_life: Option<u32>,
+    /// Directory with asset overrides.
+    #[arg(long, value_name = "DIR")]
+    assets: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+struct PlayArgs {
+    #[command(flatten)]
+    game: GameArgs,
+    /// Key bindings file. Defaults to `keys.txt` in the user's config directory.
+    #[arg(long, value_name = "FILE")]
+    keys: Option<PathBuf>,
+    /// Records the last game played to a file, to be watched with `invaders replay`.
+    #[arg(long, value_name = "FILE")]
+    record: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+struct ReplayArgs {
+    /// Recording to watch.
+    file: PathBuf,
+    /// Directory with asset overrides.
+    #[arg(long, value_name = "DIR")]
+    assets: Option<PathBuf>,
+    /// Key bindings file, used for the key that ends the replay.
+    #[arg(long, value_name = "FILE")]
+    keys: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+struct SimulateArgs {
+    #[command(flatten)]
+    game: GameArgs,
+    /// Plays a recording back instead of letting the autopilot play.
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["seed", "difficulty", "config", "overrides", "extra_life", "games"])]
+    recording: Option<PathBuf>,
+    /// Number of games, each one seeded with the previous seed plus one.
+    #[arg(long, default_value_t = 1)]
+    games: u32,
+    /// Ticks after which a game still going is stopped.
+    #[arg(long, default_value_t = 100_000)]
+    max_ticks: u64,
+    #[arg(long, default_value_t = 160, value_parser = clap::value_parser!(u16).range(Viewport::MIN_WIDTH as i64..))]
+    width: u16,
+    #[arg(long, default_value_t = 50, value_parser = clap::value_parser!(u16).range(Viewport::MIN_HEIGHT as i64..))]
+    height: u16,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let result = match cli.command {
+        None => play(cli.play),
+        Some(Command::Play(args)) => play(args),
+        Some(Command::Replay(args)) => replay(args),
+        Some(Command::Simulate(args)) => simulate(args),
+        Some(Command::ValidateAssets { dir }) => validate_assets(dir),
+        Some(Command::Version) => {
+            print_version();
+            Ok(())
+        }
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{}", format_error(&err));
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn play(args: PlayArgs) -> LigmaResult<()> {
+    let assets = Assets::load(args.game.assets.as_deref())?;
+    let mut warnings = vec![];
+    let config = load_config(&args.game, &mut warnings)?;
+    let key_bindings = load_key_bindings(args.keys)?;
+
+    let high_scores_path = HighScores::default_path();
+    let high_scores = match HighScores::load(high_scores_path.clone()) {
+        Ok(high_scores) => high_scores,
+        Err(err) => {
+            let high_scores = HighScores::corrupted(high_scores_path);
+
+            warnings.push(format!(
+                "{}, starting with an empty high score table",
+                format_error(&err)
+            ));
+            warnings.extend(
+                high_scores
+                    .get_backup_path()
+                    .map(|backup| format!("the old table is kept as '{}'", backup.display())),
+            );
+
+            high_scores
+        }
+    };
+
+    let mut game = LigmaInvaders::new(assets, args.game.seed, high_scores);
+
+    game.set_config(config);
+
+    for warning in warnings {
+        game.add_warning(warning);
+    }
+
+    if let Some(difficulty) = args.game.difficulty {
+        game.set_difficulty(difficulty);
+    }
+
+    game.set_key_bindings(key_bindings);
+
+    if let Some(file) = args.record {
+        game.set_record_path(file);
+    }
+
+    game.start().inspect_err(|_| {
+        let _ = game.reset_screen();
+    })
+}
+
+fn replay(args: ReplayArgs) -> LigmaResult<()> {
+    let assets = Assets::load(args.assets.as_deref())?;
+    let key_bindings = load_key_bindings(args.keys)?;
+    let recording = Recording::load(&args.file)?;
+
+    let mut game = LigmaInvaders::new(assets, None, HighScores::empty(None));
+    game.set_key_bindings(key_bindings);
+
+    game.replay(&recording).inspect_err(|_| {
+        let _ = game.reset_screen();
+    })
+}
+
+fn simulate(args: SimulateArgs) -> LigmaResult<()> {
+    let assets = Assets::load(args.game.assets.as_deref())?;
+
+    if let Some(file) = args.recording {
+        let recording = Recording::load(&file)?;
+        let state = recording.play(&assets)?;
+
+        println!(
+            "seed {}: {}",
+            recording.get_seed(),
+            simulate::describe(&state)
+        );
+        return Ok(());
+    }
+
+    let mut warnings = vec![];
+    let config = load_config(&args.game, &mut warnings)?
+        .with_difficulty(args.game.difficulty.unwrap_or_default());
+
+    for warning in warnings {
+        eprintln!("warning: {warning}");
+    }
+
+    let viewport = Viewport::new(args.width, args.height);
+    let first_seed = args.game.seed.unwrap_or_else(rand::random);
+    let mut total_score = 0u64;
+
+    for game in 0..args.games {
+        let seed = first_seed.wrapping_add(game as u64);
+        let state = simulate::autoplay(&assets, viewport, seed, &config, args.max_ticks);
+
+        total_score += state.get_score() as u64;
+        println!("seed {seed}: {}", simulate::describe(&state));
+    }
+
+    if args.games > 1 {
+        println!(
+            "average score over {} games: {}",
+            args.games,
+            total_score / args.games as u64
+        );
     }
+
+    Ok(())
+}
+
+fn validate_assets(dir: PathBuf) -> LigmaResult<()> {
+    Assets::load(Some(&dir))?;
+    println!("assets in '{}' are valid", dir.display());
+
+    Ok(())
+}
+
+fn print_version() {
+    let profile = match cfg!(debug_assertions) {
+        true => "debug",
+        false => "release",
+    };
+
+    println!("invaders {}", env!("CARGO_PKG_VERSION"));
+    println!(
+        "build: {profile}, {} {}",
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    );
+}
+
+/// Reads the config file and applies `--extra-life` and `--set` on top of it. Fields that are no
+/// longer used end up in `warnings`.
+fn load_config(args: &GameArgs, warnings: &mut Vec<String>) -> LigmaResult<GameConfig> {
+    let mut config = match args.config.clone().or_else(GameConfig::default_path) {
+        Some(file) => GameConfig::load(&file)?,
+        None => GameConfig::default(),
+    };
+
+    warnings.extend(config.take_legacy_fields().into_iter().map(|field| {
+        format!("config field '{field}' is ignored, the alien layout comes from formation.txt")
+    }));
+
+    if let Some(score) = args.extra_life {
+        config.extra_life_score = score;
+    }
+
+    for (field, value) in &args.overrides {
+        config.set(field, value)?;
+    }
+
+    config.validate()?;
+
+    Ok(config)
+}
+
+fn load_key_bindings(file: Option<PathBuf>) -> LigmaResult<KeyBindings> {
+    match file.or_else(KeyBindings::default_path) {
+        Some(file) => KeyBindings::load(&file),
+        None => Ok(KeyBindings::default()),
+    }
+}
+
+fn format_error(err: &LigmaError) -> String {
+    let mut message = err.to_string();
+    let mut source = err.source();
+
+    while let Some(err) = source {
+        message = format!("{message}, {err}");
+        source = err.source();
+    }
+
+    message
+}
+
+fn parse_difficulty(value: &str) -> Result<Difficulty, String> {
+    Difficulty::from_name(value).ok_or_else(|| {
+        format!("invalid difficulty '{value}', expected easy, normal, hard or arcade")
+    })
+}
+
+fn parse_override(value: &str) -> Result<(String, String), String> {
+    value
+        .split_once('=')
+        .map(|(field, value)| (field.to_string(), value.to_string()))
+        .ok_or_else(|| format!("invalid override '{value}', expected <field>=<value>"))
 }
@@ -0,0 +1,63 @@
+use ligma::{
+    assets::Assets,
+    config::GameConfig,
+    state::{Input, State, Status},
+    viewport::Viewport,
+};
+
+/// Plays a game with the autopilot until it is lost or `max_ticks` have passed.
+pub fn autoplay(
+    assets: &Assets,
+    viewport: Viewport,
+    seed: u64,
+    config: &GameConfig,
+    max_ticks: u64,
+) -> State {
+    let mut state = State::with_config(assets, viewport, seed, config.clone());
+
+    while state.get_status() == Status::Playing && state.get_tick() < max_ticks {
+        let input = autopilot(&state);
+        state.step(&input);
+    }
+
+    state
+}
+
+pub fn describe(state: &State) -> String {
+    let outcome = match state.get_status() {
+        Status::Lost => "lost",
+        Status::Playing => "still playing",
+    };
+
+    format!(
+        "score {}, wave {}, {} ticks, {outcome}",
+        state.get_score(),
+        state.get_wave(),
+        state.get_tick()
+    )
+}
+
+/// Keeps firing while chasing the lowest alien, the one closest to landing.
+fn autopilot(state: &State) -> Input {
+    let Some(player_x) = state.player.position.first().map(|p| p.x) else {
+        return Input::default();
+    };
+
+    let target = state
+        .aliens
+        .aliens_rows
+        .iter()
+        .flat_map(|row| &row.aliens)
+        .filter_map(|alien| alien.position.first())
+        .min_by_key(|p| (std::cmp::Reverse(p.y), p.x.abs_diff(player_x)));
+
+    let Some(target) = target else {
+        return Input::default();
+    };
+
+    Input {
+        left: target.x + 1 < player_x,
+        right: target.x > player_x + 1,
+        fire: true,
+    }
+}
@@ -0,0 +1,477 @@
+use std::{fs, path::Path, sync::Arc};
+
+use crate::{
+    formation::{parse_formation, Formation, Species},
+    ligma_result::{AssetErrorKind, LigmaError, LigmaResult},
+    sprite::{Frame, Sprite},
+    state::{Aliens, Coord, RgbColor, Style},
+    viewport::Viewport,
+};
+
+const FRAME_SEPARATOR: &str = "---";
+const STYLE_ATTRIBUTES: [&str; 5] = ["bold", "dim", "italic", "underlined", "reversed"];
+
+/// Sprites and the alien formation used by the game. Each one is read from an override directory
+/// when the file exists there and taken from the embedded set otherwise.
+#[derive(Debug, Clone)]
+pub struct Assets {
+    pub player: Vec<Coord>,
+    pub player_explosion: Arc<Sprite>,
+    pub laser: Vec<Coord>,
+    pub squid: Arc<Sprite>,
+    pub crab: Arc<Sprite>,
+    pub octopus: Arc<Sprite>,
+    pub bunker: Vec<Coord>,
+    pub ufo: Vec<Coord>,
+    pub alien_explosion: Arc<Sprite>,
+    pub spark: Arc<Sprite>,
+    pub formation: Formation,
+    fingerprint: u64,
+}
+
+/// FNV-1a over the asset files, stable across platforms and Rust versions unlike `std::hash`.
+struct Fingerprint(u64);
+
+impl Assets {
+    pub fn embedded() -> LigmaResult<Assets> {
+        Self::load(None)
+    }
+
+    pub fn load(directory: Option<&Path>) -> LigmaResult<Assets> {
+        if let Some(directory) = directory {
+            if !directory.is_dir() {
+                return Err(LigmaError::config(
+                    "assets",
+                    format!("'{}' is not a directory", directory.display()),
+                ));
+            }
+        }
+
+        let mut fingerprint = Fingerprint::new();
+        let fingerprint = &mut fingerprint;
+
+        let assets = Assets {
+            player: load_prototype(
+                fingerprint,
+                directory,
+                "player.txt",
+                include_str!("./assets/player.txt"),
+                1,
+            )?,
+            player_explosion: load_sprite(
+                fingerprint,
+                directory,
+                "player_explosion.txt",
+                include_str!("./assets/player_explosion.txt"),
+                1,
+            )?,
+            laser: load_prototype(
+                fingerprint,
+                directory,
+                "laser.txt",
+                include_str!("./assets/laser.txt"),
+                2,
+            )?,
+            squid: load_sprite(
+                fingerprint,
+                directory,
+                "squid.txt",
+                include_str!("./assets/squid.txt"),
+                1,
+            )?,
+            crab: load_sprite(
+                fingerprint,
+                directory,
+                "crab.txt",
+                include_str!("./assets/crab.txt"),
+                1,
+            )?,
+            octopus: load_sprite(
+                fingerprint,
+                directory,
+                "octopus.txt",
+                include_str!("./assets/octopus.txt"),
+                1,
+            )?,
+            bunker: load_prototype(
+                fingerprint,
+                directory,
+                "bunker.txt",
+                include_str!("./assets/bunker.txt"),
+                1,
+            )?,
+            ufo: load_prototype(
+                fingerprint,
+                directory,
+                "ufo.txt",
+                include_str!("./assets/ufo.txt"),
+                1,
+            )?,
+            alien_explosion: load_sprite(
+                fingerprint,
+                directory,
+                "alien_explosion.txt",
+                include_str!("./assets/alien_explosion.txt"),
+                1,
+            )?,
+            spark: load_sprite(
+                fingerprint,
+                directory,
+                "spark.txt",
+                include_str!("./assets/spark.txt"),
+                1,
+            )?,
+            formation: load_formation(fingerprint, directory)?,
+            fingerprint: fingerprint.0,
+        };
+
+        let (bottom, limit) = Aliens::get_first_wave_bounds(&assets);
+
+        if bottom > limit as u32 {
+            let file = directory
+                .map(|d| d.join("formation.txt"))
+                .filter(|p| p.exists())
+                .map_or("formation.txt".to_string(), |p| p.display().to_string());
+
+            return Err(LigmaError::AssetParsing {
+                file,
+                line: 0,
+                text: String::new(),
+                kind: AssetErrorKind::FormationTooTall { bottom, limit },
+            });
+        }
+
+        Ok(assets)
+    }
+
+    /// Changes whenever any asset file does, so recordings can tell they are replayed with the
+    /// assets they were played with.
+    pub fn get_fingerprint(&self) -> u64 {
+        self.fingerprint
+    }
+
+    pub fn get_species_sprite(&self, species: Species) -> &Arc<Sprite> {
+        match species {
+            Species::Squid => &self.squid,
+            Species::Crab => &self.crab,
+            Species::Octopus => &self.octopus,
+        }
+    }
+}
+
+impl Fingerprint {
+    fn new() -> Fingerprint {
+        Fingerprint(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn add(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+fn load_formation(
+    fingerprint: &mut Fingerprint,
+    directory: Option<&Path>,
+) -> LigmaResult<Formation> {
+    let (file, content) = read_asset(
+        fingerprint,
+        directory,
+        "formation.txt",
+        include_str!("./assets/formation.txt"),
+    )?;
+
+    parse_formation(&file, &content)
+}
+
+fn load_prototype(
+    fingerprint: &mut Fingerprint,
+    directory: Option<&Path>,
+    file: &str,
+    embedded: &str,
+    min_cells: usize,
+) -> LigmaResult<Vec<Coord>> {
+    let (file, content) = read_asset(fingerprint, directory, file, embedded)?;
+    let prototype = parse_prototype(&file, &content)?;
+
+    check_cells(&file, &prototype, min_cells)?;
+
+    Ok(prototype)
+}
+
+fn load_sprite(
+    fingerprint: &mut Fingerprint,
+    directory: Option<&Path>,
+    file: &str,
+    embedded: &str,
+    min_cells: usize,
+) -> LigmaResult<Arc<Sprite>> {
+    let (file, content) = read_asset(fingerprint, directory, file, embedded)?;
+    let sprite = parse_sprite(&file, &content)?;
+
+    for frame in &sprite.frames {
+        check_cells(&file, &frame.cells, min_cells)?;
+    }
+
+    Ok(Arc::new(sprite))
+}
+
+fn read_asset(
+    fingerprint: &mut Fingerprint,
+    directory: Option<&Path>,
+    file: &str,
+    embedded: &str,
+) -> LigmaResult<(String, String)> {
+    let path = directory.map(|d| d.join(file)).filter(|p| p.exists());
+
+    let (location, content) = match path {
+        Some(path) => {
+            let file = path.display().to_string();
+            let content = fs::read_to_string(&path).map_err(|source| LigmaError::AssetLoading {
+                file: file.clone(),
+                source,
+            })?;
+
+            (file, content)
+        }
+        None => (file.to_string(), embedded.to_string()),
+    };
+
+    // Keyed by asset name, so the same files in another directory give the same fingerprint.
+    fingerprint.add(file.as_bytes());
+    fingerprint.add(&[0]);
+    fingerprint.add(content.as_bytes());
+    fingerprint.add(&[0]);
+
+    Ok((location, content))
+}
+
+fn check_cells(file: &str, cells: &[Coord], min_cells: usize) -> LigmaResult<()> {
+    if cells.len() < min_cells {
+        return Err(LigmaError::AssetParsing {
+            file: file.to_string(),
+            line: 0,
+            text: String::new(),
+            kind: AssetErrorKind::TooFewCells {
+                min: min_cells,
+                found: cells.len(),
+            },
+        });
+    }
+
+    Ok(())
+}
+
+/// Parses a single frame: one `x y [char] [style...]` cell per line, within the smallest screen.
+/// The char defaults to a space, only the first character of a longer one is used. Style tokens
+/// are `fg=#rrggbb`, `bg=#rrggbb` and the attributes `bold`, `dim`, `italic`, `underlined` and
+/// `reversed`.
+pub fn parse_prototype(file: &str, content: &str) -> LigmaResult<Vec<Coord>> {
+    content
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| parse_coord(file, idx + 1, line))
+        .collect()
+}
+
+/// Parses an animated sprite. Frames are separated by `--- [duration]` lines, where the duration
+/// is the number of animation steps the following frame is shown for (1 by default). Cells before
+/// the first separator make up the first frame, so a file without separators is a single frame.
+pub fn parse_sprite(file: &str, content: &str) -> LigmaResult<Sprite> {
+    let mut frames = vec![];
+    let mut cells = vec![];
+    let mut duration = 1;
+
+    for (idx, line) in content.lines().enumerate() {
+        let Some(separator) = line.trim_start().strip_prefix(FRAME_SEPARATOR) else {
+            cells.push(parse_coord(file, idx + 1, line)?);
+            continue;
+        };
+
+        if !cells.is_empty() || !frames.is_empty() {
+            frames.push(Frame {
+                cells: std::mem::take(&mut cells),
+                duration,
+            });
+        }
+
+        duration = match separator.trim() {
+            "" => 1,
+            value => value
+                .parse::<u16>()
+                .ok()
+                .filter(|&duration| duration > 0)
+                .ok_or_else(|| LigmaError::AssetParsing {
+                    file: file.to_string(),
+                    line: idx + 1,
+                    text: line.to_string(),
+                    kind: AssetErrorKind::InvalidFrameDuration,
+                })?,
+        };
+    }
+
+    frames.push(Frame { cells, duration });
+
+    Ok(Sprite { frames })
+}
+
+fn parse_coord(file: &str, line_number: usize, line: &str) -> LigmaResult<Coord> {
+    let error = |kind| LigmaError::AssetParsing {
+        file: file.to_string(),
+        line: line_number,
+        text: line.to_string(),
+        kind,
+    };
+
+    let mut parts = line.split_whitespace();
+    let mut next_coordinate = || {
+        parts
+            .next()
+            .ok_or_else(|| error(AssetErrorKind::MissingCoordinate))?
+            .parse::<u16>()
+            .map_err(|err| error(AssetErrorKind::InvalidCoordinate(err)))
+    };
+
+    let x = next_coordinate()?;
+    let y = next_coordinate()?;
+
+    if x >= Viewport::MIN_WIDTH || y >= Viewport::MIN_HEIGHT {
+        return Err(error(AssetErrorKind::CellOutOfBounds {
+            width: Viewport::MIN_WIDTH,
+            height: Viewport::MIN_HEIGHT,
+        }));
+    }
+
+    let mut parts = parts.peekable();
+    let ch = parts
+        .next_if(|part| part.chars().count() == 1 || !is_style(part))
+        .and_then(|part| part.chars().next())
+        .unwrap_or(' ');
+
+    let mut style = Style::PLAIN;
+
+    for part in parts {
+        let invalid_style = || error(AssetErrorKind::InvalidStyle(part.to_string()));
+
+        match part.split_once('=') {
+            Some(("fg", value)) => {
+                style.foreground = Some(parse_color(value).ok_or_else(invalid_style)?)
+            }
+            Some(("bg", value)) => {
+                style.background = Some(parse_color(value).ok_or_else(invalid_style)?)
+            }
+            Some(_) => return Err(invalid_style()),
+            None => match part {
+                "bold" => style.attributes.bold = true,
+                "dim" => style.attributes.dim = true,
+                "italic" => style.attributes.italic = true,
+                "underlined" => style.attributes.underlined = true,
+                "reversed" => style.attributes.reversed = true,
+                _ => return Err(invalid_style()),
+            },
+        }
+    }
+
+    Ok(Coord { x, y, ch, style })
+}
+
+/// Whether the token after the coordinates is a style. Anything else is the char, as in files
+/// written before styles existed.
+fn is_style(part: &str) -> bool {
+    part.contains('=') || STYLE_ATTRIBUTES.contains(&part)
+}
+
+fn parse_color(value: &str) -> Option<RgbColor> {
+    let hex = value.strip_prefix('#')?;
+
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+
+    let channel = |idx: usize| u8::from_str_radix(&hex[idx..idx + 2], 16).ok();
+
+    Some(RgbColor {
+        r: channel(0)?,
+        g: channel(2)?,
+        b: channel(4)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_chars(frame: &Frame) -> Vec<(u16, u16, char)> {
+        frame.cells.iter().map(|c| (c.x, c.y, c.ch)).collect()
+    }
+
+    #[test]
+    fn parses_frames_and_durations() {
+        let content = "0 0 a\n1 0 b fg=#ff0000 bold\n--- 3\n0 1 c\n---\n2 2\n";
+        let sprite = parse_sprite("squid.txt", content).unwrap();
+
+        let durations: Vec<u16> = sprite.frames.iter().map(|f| f.duration).collect();
+        assert_eq!(durations, [1, 3, 1]);
+        assert_eq!(get_chars(&sprite.frames[0]), [(0, 0, 'a'), (1, 0, 'b')]);
+        assert_eq!(get_chars(&sprite.frames[1]), [(0, 1, 'c')]);
+        assert_eq!(get_chars(&sprite.frames[2]), [(2, 2, ' ')]);
+
+        let style = sprite.frames[0].cells[1].style;
+        assert_eq!(
+            style.foreground,
+            Some(RgbColor {
+                r: 0xff,
+                g: 0,
+                b: 0
+            })
+        );
+        assert!(style.attributes.bold);
+    }
+
+    #[test]
+    fn a_leading_separator_sets_the_first_duration() {
+        let sprite = parse_sprite("squid.txt", "--- 4\n0 0 a\n--- 2\n0 0 b\n").unwrap();
+
+        let durations: Vec<u16> = sprite.frames.iter().map(|f| f.duration).collect();
+        assert_eq!(durations, [4, 2]);
+    }
+
+    #[test]
+    fn reads_single_frame_files_as_before() {
+        let sprite = parse_sprite("squid.txt", "3 0 ab\n4 0 ❤️\n5 0 bold\n").unwrap();
+
+        assert_eq!(sprite.frames.len(), 1);
+        assert_eq!(
+            get_chars(&sprite.frames[0]),
+            [(3, 0, 'a'), (4, 0, '❤'), (5, 0, ' ')]
+        );
+        assert!(sprite.frames[0].cells[2].style.attributes.bold);
+    }
+
+    #[test]
+    fn reports_the_bad_line() {
+        let error = |content| match parse_sprite("squid.txt", content) {
+            Err(LigmaError::AssetParsing { line, kind, .. }) => (line, kind),
+            result => panic!("unexpected result: {result:?}"),
+        };
+
+        assert!(matches!(
+            error("0 0 a\n--- 0\n0 0 b\n"),
+            (2, AssetErrorKind::InvalidFrameDuration)
+        ));
+        assert!(matches!(
+            error("0 0 a\n0 0 b fg=red\n"),
+            (2, AssetErrorKind::InvalidStyle(_))
+        ));
+        assert!(matches!(
+            error("0 0 a\n65530 0 x\n"),
+            (2, AssetErrorKind::CellOutOfBounds { .. })
+        ));
+        assert!(matches!(
+            error("0\n"),
+            (1, AssetErrorKind::MissingCoordinate)
+        ));
+    }
+}
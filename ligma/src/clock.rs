@@ -0,0 +1,88 @@
+use std::{
+    cell::Cell,
+    fmt::Debug,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+pub type SharedClock = Rc<dyn Clock>;
+
+/// Source of simulation time. `now` is the time elapsed since the clock's own epoch and must
+/// never go backwards.
+pub trait Clock: Debug {
+    fn now(&self) -> Duration;
+}
+
+#[derive(Debug)]
+pub struct SystemClock {
+    start: Instant,
+}
+
+impl SystemClock {
+    pub fn new() -> SystemClock {
+        SystemClock {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+/// Clock that only moves when told to, for driving the game tick by tick.
+#[derive(Debug, Default)]
+pub struct ManualClock {
+    now: Cell<Duration>,
+}
+
+impl ManualClock {
+    pub fn new() -> ManualClock {
+        ManualClock::default()
+    }
+
+    pub fn advance(&self, by: Duration) {
+        self.now.set(self.now.get() + by);
+    }
+
+    pub fn set(&self, now: Duration) {
+        if now > self.now.get() {
+            self.now.set(now);
+        }
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        self.now.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manual_clock_only_moves_forward_when_told() {
+        let clock = ManualClock::new();
+        assert_eq!(clock.now(), Duration::ZERO);
+
+        clock.advance(Duration::from_millis(10));
+        clock.advance(Duration::from_millis(5));
+        assert_eq!(clock.now(), Duration::from_millis(15));
+
+        clock.set(Duration::from_millis(8));
+        assert_eq!(clock.now(), Duration::from_millis(15));
+
+        clock.set(Duration::from_millis(40));
+        assert_eq!(clock.now(), Duration::from_millis(40));
+    }
+}
@@ -0,0 +1,540 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    formation::Species,
+    ligma_result::{LigmaError, LigmaResult},
+    viewport::Viewport,
+};
+
+/// Gameplay tuning. Fields missing from a config file keep their defaults. Speeds are in cells per
+/// move, `slower_than_cycle` values and intervals in ticks.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GameConfig {
+    /// Score at which a single extra life is granted, 0 to never grant one.
+    pub extra_life_score: u32,
+    pub player: PlayerConfig,
+    pub aliens: AliensConfig,
+    pub ufo: UfoConfig,
+    pub bunkers: BunkersConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PlayerConfig {
+    pub lives: usize,
+    pub speed: u16,
+    pub move_slower_than_cycle: u64,
+    pub laser_speed: u16,
+    pub laser_slower_than_cycle: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AliensConfig {
+    pub slower_than_cycle: u64,
+    /// How much faster every wave after the first one marches.
+    pub wave_speedup: u64,
+    pub min_slower_than_cycle: u64,
+    pub max_lasers_at_a_time: usize,
+    pub shots_min_interval: u64,
+    pub laser_speed: u16,
+    pub laser_slower_than_cycle: u64,
+    pub squid_points: u32,
+    pub crab_points: u32,
+    pub octopus_points: u32,
+    /// Layout fields from before the formation file. Old config files still load, see
+    /// `GameConfig::take_legacy_fields`.
+    #[serde(skip_serializing)]
+    number: Option<u16>,
+    #[serde(skip_serializing)]
+    rows_number: Option<usize>,
+    #[serde(skip_serializing)]
+    step: Option<u16>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UfoConfig {
+    pub slower_than_cycle: u64,
+    pub min_spawn_delay: u64,
+    pub max_spawn_delay: u64,
+    /// Points for a hit, one of them picked at random.
+    pub bonuses: Vec<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BunkersConfig {
+    pub number: u16,
+}
+
+/// Named presets applied on top of the config. `Normal` plays the config as is.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    /// A single life against a fast, trigger-happy formation.
+    Arcade,
+}
+
+/// How a difficulty changes the config. Percentages scale march speed and the fire interval, the
+/// rest is added to the configured values.
+struct Preset {
+    march_percent: u64,
+    shots_interval_percent: u64,
+    lasers: i64,
+    lives: i64,
+    bunkers: i64,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            extra_life_score: 1500,
+            player: PlayerConfig::default(),
+            aliens: AliensConfig::default(),
+            ufo: UfoConfig::default(),
+            bunkers: BunkersConfig::default(),
+        }
+    }
+}
+
+impl Default for PlayerConfig {
+    fn default() -> Self {
+        PlayerConfig {
+            lives: 3,
+            speed: 2,
+            move_slower_than_cycle: 3,
+            laser_speed: 1,
+            laser_slower_than_cycle: 1,
+        }
+    }
+}
+
+impl Default for AliensConfig {
+    fn default() -> Self {
+        AliensConfig {
+            slower_than_cycle: 100,
+            wave_speedup: 10,
+            min_slower_than_cycle: 40,
+            max_lasers_at_a_time: 3,
+            shots_min_interval: 200,
+            laser_speed: 2,
+            laser_slower_than_cycle: 25,
+            squid_points: 30,
+            crab_points: 20,
+            octopus_points: 10,
+            number: None,
+            rows_number: None,
+            step: None,
+        }
+    }
+}
+
+impl Default for UfoConfig {
+    fn default() -> Self {
+        UfoConfig {
+            slower_than_cycle: 4,
+            min_spawn_delay: 1500,
+            max_spawn_delay: 3000,
+            bonuses: vec![50, 100, 150, 300],
+        }
+    }
+}
+
+impl Default for BunkersConfig {
+    fn default() -> Self {
+        BunkersConfig { number: 4 }
+    }
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Normal,
+        Difficulty::Hard,
+        Difficulty::Arcade,
+    ];
+
+    /// Name used on the command line and in the high scores file.
+    pub fn get_name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+            Difficulty::Arcade => "arcade",
+        }
+    }
+
+    pub fn get_label(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "EASY",
+            Difficulty::Normal => "NORMAL",
+            Difficulty::Hard => "HARD",
+            Difficulty::Arcade => "ARCADE",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Difficulty> {
+        Self::ALL
+            .into_iter()
+            .find(|difficulty| difficulty.get_name() == name)
+    }
+
+    /// The next harder preset, or the easiest one after the hardest.
+    pub fn next(self) -> Difficulty {
+        let idx = Self::ALL.iter().position(|&d| d == self).unwrap_or(0);
+        Self::ALL[(idx + 1) % Self::ALL.len()]
+    }
+
+    pub fn previous(self) -> Difficulty {
+        let idx = Self::ALL.iter().position(|&d| d == self).unwrap_or(0);
+        Self::ALL[(idx + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+
+    fn get_preset(self) -> Preset {
+        match self {
+            Difficulty::Easy => Preset {
+                march_percent: 150,
+                shots_interval_percent: 150,
+                lasers: -1,
+                lives: 2,
+                bunkers: 1,
+            },
+            Difficulty::Normal => Preset {
+                march_percent: 100,
+                shots_interval_percent: 100,
+                lasers: 0,
+                lives: 0,
+                bunkers: 0,
+            },
+            Difficulty::Hard => Preset {
+                march_percent: 75,
+                shots_interval_percent: 60,
+                lasers: 1,
+                lives: -1,
+                bunkers: -1,
+            },
+            Difficulty::Arcade => Preset {
+                march_percent: 60,
+                shots_interval_percent: 50,
+                lasers: 2,
+                lives: -2,
+                bunkers: -2,
+            },
+        }
+    }
+}
+
+impl AliensConfig {
+    pub fn get_points(&self, species: Species) -> u32 {
+        match species {
+            Species::Squid => self.squid_points,
+            Species::Crab => self.crab_points,
+            Species::Octopus => self.octopus_points,
+        }
+    }
+}
+
+impl GameConfig {
+    const MAX_TICKS: u64 = u32::MAX as u64;
+    const MAX_COUNT: u64 = 100;
+    /// One column per bunker on the smallest viewport. Wider bunkers are laid out as many as fit.
+    const MAX_BUNKERS: u64 = Viewport::MIN_WIDTH as u64 - 1;
+
+    /// `config.toml` in the user's config directory, if there is one.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("ligma-invaders").join("config.toml"))
+    }
+
+    /// Reads the config from `file`. A missing file is the default config.
+    pub fn load(file: &Path) -> LigmaResult<GameConfig> {
+        let content = match fs::read_to_string(file) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(source) => {
+                return Err(LigmaError::ConfigLoading {
+                    file: file.display().to_string(),
+                    source,
+                })
+            }
+        };
+
+        let config: GameConfig =
+            toml::from_str(&content).map_err(|source| LigmaError::ConfigParsing {
+                file: file.display().to_string(),
+                source,
+            })?;
+
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    /// Clears fields that are still accepted but no longer used and returns their names.
+    pub fn take_legacy_fields(&mut self) -> Vec<&'static str> {
+        let aliens = &mut self.aliens;
+
+        [
+            ("aliens.number", aliens.number.take().is_some()),
+            ("aliens.rows_number", aliens.rows_number.take().is_some()),
+            ("aliens.step", aliens.step.take().is_some()),
+        ]
+        .into_iter()
+        .filter_map(|(field, set)| set.then_some(field))
+        .collect()
+    }
+
+    /// Overrides a single field, e.g. `set("aliens.wave_speedup", "20")`. `value` is a TOML value.
+    pub fn set(&mut self, field: &str, value: &str) -> LigmaResult<()> {
+        let error = |message: &str| LigmaError::config(field, message);
+
+        let value = format!("value = {value}")
+            .parse::<toml::Table>()
+            .ok()
+            .and_then(|mut table| table.remove("value"))
+            .ok_or_else(|| error("not a valid TOML value"))?;
+        let mut config =
+            toml::Table::try_from(&*self).map_err(|_| error("config can't be serialized"))?;
+
+        let (table, name) = match field.split_once('.') {
+            Some((section, name)) => match config.get_mut(section) {
+                Some(toml::Value::Table(table)) => (table, name),
+                _ => return Err(error("unknown field")),
+            },
+            None => (&mut config, field),
+        };
+
+        match table.get_mut(name) {
+            Some(toml::Value::Table(_)) | None => return Err(error("unknown field")),
+            Some(entry) => *entry = value,
+        }
+
+        *self = config
+            .try_into()
+            .map_err(|err: toml::de::Error| error(err.message()))?;
+
+        Ok(())
+    }
+
+    /// The config adjusted for `difficulty`. A valid config stays valid: lives, bunkers and lasers
+    /// never drop below 1, and disabled alien fire stays disabled.
+    pub fn with_difficulty(&self, difficulty: Difficulty) -> GameConfig {
+        let preset = difficulty.get_preset();
+        let scale = |value: u64, percent: u64| (value * percent / 100).max(1);
+        let add = |value: u64, delta: i64| match value {
+            0 => 0,
+            value => value.saturating_add_signed(delta).max(1),
+        };
+
+        let mut config = self.clone();
+
+        config.aliens.slower_than_cycle =
+            scale(config.aliens.slower_than_cycle, preset.march_percent);
+        config.aliens.min_slower_than_cycle =
+            scale(config.aliens.min_slower_than_cycle, preset.march_percent);
+        config.aliens.shots_min_interval = scale(
+            config.aliens.shots_min_interval,
+            preset.shots_interval_percent,
+        );
+        config.aliens.max_lasers_at_a_time =
+            add(config.aliens.max_lasers_at_a_time as u64, preset.lasers) as usize;
+        config.player.lives = add(config.player.lives as u64, preset.lives) as usize;
+        config.bunkers.number = add(config.bunkers.number as u64, preset.bunkers) as u16;
+
+        config
+    }
+
+    /// Rejects values the simulation can't run with, naming the first bad field.
+    pub fn validate(&self) -> LigmaResult<()> {
+        let at_least_one = [
+            ("player.lives", self.player.lives as u64),
+            ("player.speed", self.player.speed as u64),
+            (
+                "player.move_slower_than_cycle",
+                self.player.move_slower_than_cycle,
+            ),
+            ("player.laser_speed", self.player.laser_speed as u64),
+            (
+                "player.laser_slower_than_cycle",
+                self.player.laser_slower_than_cycle,
+            ),
+            (
+                "aliens.min_slower_than_cycle",
+                self.aliens.min_slower_than_cycle,
+            ),
+            ("aliens.laser_speed", self.aliens.laser_speed as u64),
+            (
+                "aliens.laser_slower_than_cycle",
+                self.aliens.laser_slower_than_cycle,
+            ),
+            ("ufo.slower_than_cycle", self.ufo.slower_than_cycle),
+            ("bunkers.number", self.bunkers.number as u64),
+        ];
+
+        if let Some((field, _)) = at_least_one.iter().find(|(_, value)| *value == 0) {
+            return Err(LigmaError::config(*field, "must be at least 1"));
+        }
+
+        // Speeds move things by at most a screen per step, tick counts stay small enough for the
+        // difficulty presets to scale, and every bunker gets a slice of the smallest viewport.
+        let at_most = [
+            ("player.lives", self.player.lives as u64, Self::MAX_COUNT),
+            (
+                "player.speed",
+                self.player.speed as u64,
+                Viewport::MIN_WIDTH as u64,
+            ),
+            (
+                "player.move_slower_than_cycle",
+                self.player.move_slower_than_cycle,
+                Self::MAX_TICKS,
+            ),
+            (
+                "player.laser_speed",
+                self.player.laser_speed as u64,
+                Viewport::MIN_HEIGHT as u64,
+            ),
+            (
+                "player.laser_slower_than_cycle",
+                self.player.laser_slower_than_cycle,
+                Self::MAX_TICKS,
+            ),
+            (
+                "aliens.slower_than_cycle",
+                self.aliens.slower_than_cycle,
+                Self::MAX_TICKS,
+            ),
+            (
+                "aliens.wave_speedup",
+                self.aliens.wave_speedup,
+                Self::MAX_TICKS,
+            ),
+            (
+                "aliens.max_lasers_at_a_time",
+                self.aliens.max_lasers_at_a_time as u64,
+                Self::MAX_COUNT,
+            ),
+            (
+                "aliens.shots_min_interval",
+                self.aliens.shots_min_interval,
+                Self::MAX_TICKS,
+            ),
+            (
+                "aliens.laser_speed",
+                self.aliens.laser_speed as u64,
+                Viewport::MIN_HEIGHT as u64,
+            ),
+            (
+                "aliens.laser_slower_than_cycle",
+                self.aliens.laser_slower_than_cycle,
+                Self::MAX_TICKS,
+            ),
+            (
+                "ufo.slower_than_cycle",
+                self.ufo.slower_than_cycle,
+                Self::MAX_TICKS,
+            ),
+            (
+                "ufo.max_spawn_delay",
+                self.ufo.max_spawn_delay,
+                Self::MAX_TICKS,
+            ),
+            (
+                "bunkers.number",
+                self.bunkers.number as u64,
+                Self::MAX_BUNKERS,
+            ),
+        ];
+
+        if let Some((field, _, max)) = at_most.iter().find(|(_, value, max)| value > max) {
+            return Err(LigmaError::config(*field, format!("must be at most {max}")));
+        }
+
+        if self.aliens.slower_than_cycle < self.aliens.min_slower_than_cycle {
+            return Err(LigmaError::config(
+                "aliens.slower_than_cycle",
+                "must not be less than 'aliens.min_slower_than_cycle'",
+            ));
+        }
+
+        if self.ufo.max_spawn_delay < self.ufo.min_spawn_delay {
+            return Err(LigmaError::config(
+                "ufo.max_spawn_delay",
+                "must not be less than 'ufo.min_spawn_delay'",
+            ));
+        }
+
+        if self.ufo.bonuses.is_empty() {
+            return Err(LigmaError::config("ufo.bonuses", "must not be empty"));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_field(result: LigmaResult<()>) -> String {
+        match result {
+            Err(LigmaError::Config { field, .. }) => field,
+            result => panic!("unexpected result: {result:?}"),
+        }
+    }
+
+    #[test]
+    fn set_writes_a_nested_field() {
+        let mut config = GameConfig::default();
+
+        config.set("aliens.wave_speedup", "20").unwrap();
+        config.set("bunkers.number", "2").unwrap();
+
+        assert_eq!(config.aliens.wave_speedup, 20);
+        assert_eq!(config.bunkers.number, 2);
+        assert_eq!(config.player, GameConfig::default().player);
+    }
+
+    #[test]
+    fn set_names_the_bad_field() {
+        let mut config = GameConfig::default();
+
+        assert_eq!(get_field(config.set("aliens.nope", "1")), "aliens.nope");
+        assert_eq!(get_field(config.set("aliens", "1")), "aliens");
+        assert_eq!(
+            get_field(config.set("player.lives", "many")),
+            "player.lives"
+        );
+        assert_eq!(
+            get_field(config.set("player.lives", "\"3\"")),
+            "player.lives"
+        );
+        assert_eq!(config.player.lives, GameConfig::default().player.lives);
+    }
+
+    #[test]
+    fn validate_names_the_bad_field() {
+        assert!(GameConfig::default().validate().is_ok());
+
+        let mut config = GameConfig::default();
+        config.player.lives = 0;
+        assert_eq!(get_field(config.validate()), "player.lives");
+
+        let mut config = GameConfig::default();
+        config.bunkers.number = Viewport::MIN_WIDTH;
+        assert_eq!(get_field(config.validate()), "bunkers.number");
+
+        let mut config = GameConfig::default();
+        config.aliens.slower_than_cycle = config.aliens.min_slower_than_cycle - 1;
+        assert_eq!(get_field(config.validate()), "aliens.slower_than_cycle");
+    }
+}
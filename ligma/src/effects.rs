@@ -0,0 +1,91 @@
+use std::sync::Arc;
+
+use crate::{
+    scheduler::{Scheduler, Tick, Timer},
+    sprite::{Animation, Sprite},
+    state::{shift_prototype, Coord, RgbColor},
+};
+
+/// Short-lived animations that play once and disappear, e.g. explosions and sparks.
+#[derive(Debug, Default)]
+pub struct Effects {
+    effects: Vec<Effect>,
+}
+
+#[derive(Debug)]
+pub struct Effect {
+    pub position: Vec<Coord>,
+    pub color: Option<RgbColor>,
+    x: u16,
+    y: u16,
+    animation: Animation,
+    timer: Timer,
+    ends_at: Tick,
+}
+
+impl Effects {
+    const FRAME_TICKS: u64 = 6;
+
+    /// Plays the sprite once with its top left corner at `x`, `y` and returns the tick it ends
+    /// at. `color` is used for cells without a color of their own.
+    pub fn spawn(
+        &mut self,
+        sprite: &Arc<Sprite>,
+        x: u16,
+        y: u16,
+        color: Option<RgbColor>,
+        scheduler: &Scheduler,
+    ) -> Tick {
+        let animation = Animation::new(Arc::clone(sprite));
+        let ends_at = scheduler.now() + sprite.get_duration() * Self::FRAME_TICKS;
+
+        self.effects.push(Effect {
+            position: shift_prototype(&animation.get_frame().cells, x, y),
+            color,
+            x,
+            y,
+            animation,
+            timer: scheduler.timer(Self::FRAME_TICKS),
+            ends_at,
+        });
+
+        ends_at
+    }
+
+    /// Like `spawn`, centered on `x`, `y`.
+    pub fn spawn_centered(
+        &mut self,
+        sprite: &Arc<Sprite>,
+        x: u16,
+        y: u16,
+        color: Option<RgbColor>,
+        scheduler: &Scheduler,
+    ) -> Tick {
+        let cells = &sprite.get_first_frame().cells;
+        let width = cells.iter().map(|c| c.x + 1).max().unwrap_or(0);
+        let height = cells.iter().map(|c| c.y + 1).max().unwrap_or(0);
+
+        self.spawn(
+            sprite,
+            x.saturating_sub(width / 2),
+            y.saturating_sub(height / 2),
+            color,
+            scheduler,
+        )
+    }
+
+    pub fn update(&mut self, now: Tick) {
+        self.effects.retain(|effect| now < effect.ends_at);
+
+        for effect in self.effects.iter_mut() {
+            if effect.timer.fire(now) && effect.animation.advance() {
+                effect.position =
+                    shift_prototype(&effect.animation.get_frame().cells, effect.x, effect.y);
+            }
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Effect> {
+        self.effects.iter()
+    }
+}
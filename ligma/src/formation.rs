@@ -0,0 +1,198 @@
+use crate::ligma_result::{AssetErrorKind, LigmaError, LigmaResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Species {
+    Squid,
+    Crab,
+    Octopus,
+}
+
+/// Layout of a wave. Rows march one after another, the bottom one first, each `stagger` ticks
+/// after the one below it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Formation {
+    /// Species of each row, top row first.
+    pub rows: Vec<Species>,
+    /// Aliens per row on a wide enough screen.
+    pub columns: u16,
+    /// Horizontal distance between two aliens in a row.
+    pub spacing: u16,
+    /// Vertical distance between two rows.
+    pub row_spacing: u16,
+    pub offset_x: u16,
+    /// Where the first wave starts. Later waves start lower.
+    pub offset_y: u16,
+    pub stagger: u64,
+}
+
+impl Species {
+    pub const ALL: [Species; 3] = [Species::Squid, Species::Crab, Species::Octopus];
+
+    /// Name used in the formation file.
+    pub fn get_name(self) -> &'static str {
+        match self {
+            Species::Squid => "squid",
+            Species::Crab => "crab",
+            Species::Octopus => "octopus",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Species> {
+        Self::ALL
+            .into_iter()
+            .find(|species| species.get_name() == name)
+    }
+}
+
+/// Parses a formation: one `directive value...` per line, blank lines and lines starting with `#`
+/// are skipped. `columns`, `spacing`, `row_spacing`, `offset <x> <y>` and `stagger` are required,
+/// followed by at least one `row <species>` line.
+pub fn parse_formation(file: &str, content: &str) -> LigmaResult<Formation> {
+    let mut rows = vec![];
+    let mut columns = None;
+    let mut spacing = None;
+    let mut row_spacing = None;
+    let mut offset = None;
+    let mut stagger = None;
+
+    for (idx, line) in content.lines().enumerate() {
+        let error = |kind| LigmaError::AssetParsing {
+            file: file.to_string(),
+            line: idx + 1,
+            text: line.to_string(),
+            kind,
+        };
+
+        let mut parts = line.split_whitespace();
+
+        let Some(directive) = parts.next().filter(|part| !part.starts_with('#')) else {
+            continue;
+        };
+
+        let values = parts.collect::<Vec<_>>();
+        let invalid = || error(AssetErrorKind::InvalidFormationValue(directive.to_string()));
+        let number = |min: u16| match values[..] {
+            [value] => value
+                .parse::<u16>()
+                .ok()
+                .filter(|&value| value >= min)
+                .ok_or_else(invalid),
+            _ => Err(invalid()),
+        };
+
+        match directive {
+            "row" => match values[..] {
+                [name] => rows.push(
+                    Species::from_name(name)
+                        .ok_or_else(|| error(AssetErrorKind::UnknownSpecies(name.to_string())))?,
+                ),
+                _ => return Err(invalid()),
+            },
+            "columns" => columns = Some(number(1)?),
+            "spacing" => spacing = Some(number(1)?),
+            "row_spacing" => row_spacing = Some(number(1)?),
+            "stagger" => stagger = Some(number(0)? as u64),
+            "offset" => match values[..] {
+                [x, y] => {
+                    offset = Some((
+                        x.parse().map_err(|_| invalid())?,
+                        y.parse().map_err(|_| invalid())?,
+                    ))
+                }
+                _ => return Err(invalid()),
+            },
+            _ => {
+                return Err(error(AssetErrorKind::UnknownFormationDirective(
+                    directive.to_string(),
+                )))
+            }
+        }
+    }
+
+    let missing = |directive| LigmaError::AssetParsing {
+        file: file.to_string(),
+        line: 0,
+        text: String::new(),
+        kind: AssetErrorKind::MissingFormationDirective(directive),
+    };
+
+    if rows.is_empty() {
+        return Err(missing("row"));
+    }
+
+    let (offset_x, offset_y) = offset.ok_or_else(|| missing("offset"))?;
+
+    Ok(Formation {
+        rows,
+        columns: columns.ok_or_else(|| missing("columns"))?,
+        spacing: spacing.ok_or_else(|| missing("spacing"))?,
+        row_spacing: row_spacing.ok_or_else(|| missing("row_spacing"))?,
+        offset_x,
+        offset_y,
+        stagger: stagger.ok_or_else(|| missing("stagger"))?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_a_formation() {
+        let content = "# two rows\n\
+                       columns 4\n\
+                       spacing 12\n\
+                       \n\
+                       row_spacing 5\n\
+                       offset 2 6\n\
+                       stagger 10\n\
+                       row squid\n\
+                       row octopus\n";
+
+        assert_eq!(
+            parse_formation("formation.txt", content).unwrap(),
+            Formation {
+                rows: vec![Species::Squid, Species::Octopus],
+                columns: 4,
+                spacing: 12,
+                row_spacing: 5,
+                offset_x: 2,
+                offset_y: 6,
+                stagger: 10,
+            }
+        );
+    }
+
+    #[test]
+    fn reports_the_bad_line() {
+        assert!(matches!(
+            parse_formation("formation.txt", "columns 4\nspacing 0\n"),
+            Err(LigmaError::AssetParsing {
+                line: 2,
+                kind: AssetErrorKind::InvalidFormationValue(directive),
+                ..
+            }) if directive == "spacing"
+        ));
+        assert!(matches!(
+            parse_formation("formation.txt", "row squid\nrow kraken\n"),
+            Err(LigmaError::AssetParsing {
+                line: 2,
+                kind: AssetErrorKind::UnknownSpecies(_),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn reports_a_missing_directive() {
+        let content = "columns 4\nspacing 12\nrow_spacing 5\nstagger 10\nrow crab\n";
+
+        assert!(matches!(
+            parse_formation("formation.txt", content),
+            Err(LigmaError::AssetParsing {
+                kind: AssetErrorKind::MissingFormationDirective("offset"),
+                ..
+            })
+        ));
+    }
+}
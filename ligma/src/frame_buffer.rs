@@ -0,0 +1,105 @@
+use crate::state::{Coord, RgbColor, Style};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    pub ch: char,
+    pub style: Style,
+}
+
+/// Grid of cells making up one frame.
+#[derive(Debug, Clone)]
+pub struct FrameBuffer {
+    width: u16,
+    height: u16,
+    cells: Vec<Cell>,
+}
+
+impl Cell {
+    pub const EMPTY: Cell = Cell {
+        ch: ' ',
+        style: Style::PLAIN,
+    };
+}
+
+impl FrameBuffer {
+    pub fn new(width: u16, height: u16) -> FrameBuffer {
+        FrameBuffer {
+            width,
+            height,
+            cells: vec![Cell::EMPTY; width as usize * height as usize],
+        }
+    }
+
+    pub fn get_width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn get_height(&self) -> u16 {
+        self.height
+    }
+
+    pub fn get(&self, x: u16, y: u16) -> Option<&Cell> {
+        self.index(x, y).map(|idx| &self.cells[idx])
+    }
+
+    pub fn set(&mut self, x: u16, y: u16, cell: Cell) {
+        if let Some(idx) = self.index(x, y) {
+            self.cells[idx] = cell;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.fill(Cell::EMPTY);
+    }
+
+    /// Draws the sprite with its own cell styles, using `color` for cells without a foreground.
+    pub fn draw_sprite(&mut self, sprite: &[Coord], color: Option<RgbColor>) {
+        for Coord { x, y, ch, style } in sprite {
+            let cell = Cell {
+                ch: *ch,
+                style: style.or_foreground(color),
+            };
+
+            self.set(*x, *y, cell);
+        }
+    }
+
+    pub fn draw_text(&mut self, x: u16, y: u16, text: &str, color: Option<RgbColor>) {
+        let style = Style::with_foreground(color);
+
+        for (idx, ch) in text.chars().enumerate() {
+            self.set(x.saturating_add(idx as u16), y, Cell { ch, style });
+        }
+    }
+
+    /// Characters of the frame, one string per row.
+    pub fn get_lines(&self) -> Vec<String> {
+        self.cells
+            .chunks(self.width.max(1) as usize)
+            .map(|row| row.iter().map(|c| c.ch).collect())
+            .collect()
+    }
+
+    /// Cells that differ from `previous`, row by row. Both buffers must be of the same size.
+    pub fn diff<'a>(
+        &'a self,
+        previous: &'a FrameBuffer,
+    ) -> impl Iterator<Item = (u16, u16, &'a Cell)> + 'a {
+        let width = self.width.max(1) as usize;
+
+        self.cells
+            .iter()
+            .zip(previous.cells.iter())
+            .enumerate()
+            .filter(|(_, (current, previous))| current != previous)
+            .map(move |(idx, (current, _))| ((idx % width) as u16, (idx / width) as u16, current))
+    }
+
+    fn index(&self, x: u16, y: u16) -> Option<usize> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+
+        Some(y as usize * self.width as usize + x as usize)
+    }
+}
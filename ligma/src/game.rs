@@ -1,114 +1,367 @@
 use std::{
-    io::{stdout, Result, Stdout, Write},
-    time::{Duration, SystemTime},
+    io::{stdout, Result},
+    path::PathBuf,
+    rc::Rc,
+    time::Duration,
 };
 
 use crossterm::{
     cursor,
-    event::{self, poll, read, Event, KeyCode, KeyEvent},
-    execute, queue,
-    style::{self, Color, Stylize},
+    event::{self, poll, read, Event, KeyCode, KeyEvent, KeyEventKind, KeyboardEnhancementFlags},
+    execute, style,
     terminal::{self, disable_raw_mode, enable_raw_mode},
 };
 
 use crate::{
-    ligma_result::LigmaResult,
-    state::{Coord, State},
+    assets::Assets,
+    clock::{SharedClock, SystemClock},
+    config::{Difficulty, GameConfig},
+    high_scores::{HighScore, HighScores},
+    input::InputState,
+    key_bindings::{get_keys_label, Action, KeyBindings},
+    ligma_result::{LigmaError, LigmaResult},
+    recording::{RecordedEvent, Recording},
+    renderer::{
+        render_lines, render_message, render_pause_menu, render_state, Renderer, TerminalRenderer,
+    },
+    state::{State, Status},
+    viewport::Viewport,
 };
 
 pub const MS_PER_UPDATE: u128 = 10;
 pub const MS_PER_RENDER: u128 = 40;
-pub const VIEWPORT_MIN_X: u16 = 1;
-pub const VIEWPORT_MAX_X: u16 = 200;
-pub const VIEWPORT_MIN_Y: u16 = 1;
-pub const VIEWPORT_MAX_Y: u16 = 72;
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum InputResult {
     Continue,
+    Pause,
+    Quit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PauseOption {
+    Resume,
+    Restart,
+    Controls,
     Quit,
 }
 
 #[derive(Debug)]
 pub enum GameState {
     NewGame,
-    Won,
     Lost,
 }
 
 #[derive(Debug)]
 pub struct LigmaInvaders {
-    last_update: SystemTime,
-    last_render: SystemTime,
-    std_out: Stdout,
+    last_update: Duration,
+    last_render: Duration,
+    renderer: Box<dyn Renderer>,
     state: State,
+    input: InputState,
+    key_bindings: KeyBindings,
+    clock: SharedClock,
+    assets: Assets,
+    seed: Option<u64>,
+    viewport: Viewport,
+    high_scores: HighScores,
+    warnings: Vec<String>,
+    config: GameConfig,
+    difficulty: Difficulty,
+    record_path: Option<PathBuf>,
+    recording: Option<Recording>,
 }
 
 impl LigmaInvaders {
-    pub fn new() -> LigmaResult<LigmaInvaders> {
-        Ok(LigmaInvaders {
-            last_update: SystemTime::now(),
-            last_render: SystemTime::now(),
-            std_out: stdout(),
-            state: State::new()?,
-        })
+    pub fn new(assets: Assets, seed: Option<u64>, high_scores: HighScores) -> LigmaInvaders {
+        Self::with_clock(Rc::new(SystemClock::new()), assets, seed, high_scores)
+    }
+
+    /// Without a seed every game gets a random one, otherwise every game (including restarts)
+    /// is played with the given seed.
+    pub fn with_clock(
+        clock: SharedClock,
+        assets: Assets,
+        seed: Option<u64>,
+        high_scores: HighScores,
+    ) -> LigmaInvaders {
+        let viewport = Viewport::from_terminal();
+        let renderer = TerminalRenderer::new(viewport.get_width(), viewport.get_height());
+
+        Self::with_renderer(
+            clock,
+            Box::new(renderer),
+            viewport,
+            assets,
+            seed,
+            high_scores,
+        )
+    }
+
+    /// Plays into `renderer`, which is expected to be `viewport` sized, without querying the
+    /// terminal.
+    pub fn with_renderer(
+        clock: SharedClock,
+        renderer: Box<dyn Renderer>,
+        viewport: Viewport,
+        assets: Assets,
+        seed: Option<u64>,
+        high_scores: HighScores,
+    ) -> LigmaInvaders {
+        LigmaInvaders {
+            last_update: clock.now(),
+            last_render: clock.now(),
+            renderer,
+            state: Self::new_state(
+                &assets,
+                viewport,
+                seed,
+                &GameConfig::default(),
+                Difficulty::default(),
+            ),
+            input: InputState::default(),
+            key_bindings: KeyBindings::default(),
+            clock,
+            assets,
+            seed,
+            viewport,
+            high_scores,
+            warnings: vec![],
+            config: GameConfig::default(),
+            difficulty: Difficulty::default(),
+            record_path: None,
+            recording: None,
+        }
+    }
+
+    /// Starts over with the given config, which is expected to be valid. High scores are only
+    /// kept for the default config, so that the difficulty tables compare like with like.
+    pub fn set_config(&mut self, config: GameConfig) {
+        self.config = config;
+        self.restart();
+    }
+
+    /// Preselects the difficulty on the start screen.
+    pub fn set_difficulty(&mut self, difficulty: Difficulty) {
+        self.difficulty = difficulty;
+        self.restart();
+    }
+
+    pub fn set_key_bindings(&mut self, key_bindings: KeyBindings) {
+        self.key_bindings = key_bindings;
+    }
+
+    /// Records every game to `path`, each new game replacing the previous one. The file is written
+    /// when the player quits.
+    pub fn set_record_path(&mut self, path: PathBuf) {
+        self.record_path = Some(path);
+    }
+
+    /// Shown on the next start or game over screen, since anything printed before the game starts
+    /// is hidden by the alternate screen.
+    pub fn add_warning(&mut self, warning: impl Into<String>) {
+        self.warnings.push(warning.into());
     }
 
     pub fn start(&mut self) -> LigmaResult<()> {
         self.prepare_screen()
-            .map_err(|err| format!("error while preparing the screen, {err}"))?;
+            .map_err(LigmaError::terminal("error while preparing the screen"))?;
         let start_screen_result = self.render_game_over_screen(GameState::NewGame)?;
 
         if start_screen_result == InputResult::Quit {
             return self
                 .reset_screen()
-                .map_err(|err| format!("error while resetting the screen, {err}"));
+                .map_err(LigmaError::terminal("error while resetting the screen"));
         }
 
         self.set_last_update();
 
         loop {
+            let handle_result = self
+                .poll_input()
+                .map_err(LigmaError::input("error while handling user input"))?;
+
+            let handle_result = match handle_result {
+                InputResult::Pause => self.pause()?,
+                result => result,
+            };
+
+            match handle_result {
+                InputResult::Continue | InputResult::Pause => (),
+                InputResult::Quit => break,
+            }
+
+            match self.update_and_render()? {
+                InputResult::Continue | InputResult::Pause => (),
+                InputResult::Quit => break,
+            }
+        }
+
+        self.reset_screen()
+            .map_err(LigmaError::terminal("error while resetting the screen"))?;
+
+        match (&self.record_path, &self.recording) {
+            (Some(path), Some(recording)) => recording.save(path),
+            _ => Ok(()),
+        }
+    }
+
+    /// Plays a recording back in real time on the recorded screen size. The quit and pause keys
+    /// end the replay early.
+    pub fn replay(&mut self, recording: &Recording) -> LigmaResult<()> {
+        self.state = recording.new_state(&self.assets)?;
+
+        self.prepare_screen()
+            .map_err(LigmaError::terminal("error while preparing the screen"))?;
+
+        // The recording plays at its own size, and waits while the terminal is smaller than that.
+        let mut recorded = recording.get_viewport();
+        let mut rendered = self.viewport;
+        self.set_last_update();
+
+        let mut events = recording.get_events().iter().peekable();
+
+        while events.peek().is_some() {
             if poll(Duration::from_millis(MS_PER_UPDATE as u64))
-                .map_err(|err| format!("error polling for user input, {err}"))?
+                .map_err(LigmaError::input("error polling for user input"))?
             {
-                let handle_result = self
-                    .handle_user_input()
-                    .map_err(|err| format!("error while handling user input, {err}"))?;
+                match read().map_err(LigmaError::input("error while reading players input"))? {
+                    Event::Resize(width, height) => {
+                        self.viewport = Viewport::new(width, height);
+                        self.renderer.resize(width, height);
+                        rendered = self.viewport;
+                    }
+                    Event::Key(KeyEvent {
+                        kind: KeyEventKind::Release,
+                        ..
+                    }) => (),
+                    Event::Key(KeyEvent { code, .. })
+                        if self.key_bindings.is_bound(Action::Quit, code)
+                            || self.key_bindings.is_bound(Action::Pause, code) =>
+                    {
+                        break
+                    }
+                    _ => (),
+                }
+            }
+
+            let fits = self.viewport.get_width() >= recorded.get_width()
+                && self.viewport.get_height() >= recorded.get_height();
+            let size = if fits { recorded } else { self.viewport };
+
+            if size != rendered {
+                self.renderer.resize(size.get_width(), size.get_height());
+                rendered = size;
+            }
 
-                match handle_result {
-                    InputResult::Continue => (),
-                    InputResult::Quit => break,
+            if !fits {
+                self.set_last_update();
+
+                if self.get_elapsed_since_render().as_millis() > MS_PER_RENDER {
+                    self.render_too_small_for(recorded.get_width(), recorded.get_height())?;
+                    self.last_render = self.clock.now();
                 }
+
+                continue;
             }
 
-            match self.update_and_render()? {
-                InputResult::Continue => (),
-                InputResult::Quit => break,
+            let mut lag = self.get_elapsed_since_update().as_millis();
+
+            while lag >= MS_PER_UPDATE {
+                self.set_last_update();
+                lag -= MS_PER_UPDATE;
+
+                // Resizes happen between ticks, so apply them along with the next step.
+                for event in events.by_ref() {
+                    match event {
+                        RecordedEvent::Resize(viewport) => {
+                            recorded = *viewport;
+                            self.state.resize(recorded);
+                        }
+                        RecordedEvent::Step(_) => {
+                            event.apply(&mut self.state);
+                            break;
+                        }
+                    }
+                }
+            }
+
+            if rendered == recorded && self.get_elapsed_since_render().as_millis() > MS_PER_RENDER {
+                render_state(self.renderer.as_mut(), &self.state)
+                    .map_err(LigmaError::terminal("error while rendering game state"))?;
+
+                self.last_render = self.clock.now();
+            }
+        }
+
+        self.renderer
+            .resize(self.viewport.get_width(), self.viewport.get_height());
+
+        let lines = [
+            format!(
+                "REPLAY FINISHED. SCORE: {}  WAVE: {}",
+                self.state.get_score(),
+                self.state.get_wave()
+            ),
+            String::new(),
+            "PRESS ANY KEY TO EXIT".to_string(),
+        ];
+
+        self.render_screen(&lines)?;
+
+        loop {
+            match read().map_err(LigmaError::input("error while reading players input"))? {
+                Event::Key(KeyEvent {
+                    kind: KeyEventKind::Release,
+                    ..
+                }) => (),
+                Event::Key(_) => break,
+                _ => (),
             }
         }
 
         self.reset_screen()
-            .map_err(|err| format!("error while resetting the screen, {err}"))
+            .map_err(LigmaError::terminal("error while resetting the screen"))
     }
 
     pub fn reset_screen(&mut self) -> Result<()> {
         execute!(
-            self.std_out,
+            stdout(),
             style::ResetColor,
             cursor::Show,
             event::DisableMouseCapture,
+            event::DisableFocusChange,
             terminal::LeaveAlternateScreen
         )?;
+
+        if self.input.reports_releases() {
+            execute!(stdout(), event::PopKeyboardEnhancementFlags)?;
+        }
+
         disable_raw_mode()
     }
 
     fn update_and_render(&mut self) -> LigmaResult<InputResult> {
-        if self.state.player.health == 0 || self.state.aliens_invaded() {
-            return self.render_game_over_screen(GameState::Lost);
+        if !self.viewport.fits() {
+            self.set_last_update();
+
+            if self.get_elapsed_since_render().as_millis() > MS_PER_RENDER {
+                self.render_too_small_screen()?;
+                self.last_render = self.clock.now();
+            }
+
+            return Ok(InputResult::Continue);
         }
 
-        if self.state.get_aliens_count() == 0 {
-            return self.render_game_over_screen(GameState::Won);
+        match self.state.get_status() {
+            Status::Lost => {
+                if self.record_high_score()? == InputResult::Quit {
+                    return Ok(InputResult::Quit);
+                }
+
+                return self.render_game_over_screen(GameState::Lost);
+            }
+            Status::Playing => (),
         }
 
         let mut lag = self.get_elapsed_since_update().as_millis();
@@ -117,176 +370,485 @@ impl LigmaInvaders {
             self.set_last_update();
             lag -= MS_PER_UPDATE;
 
-            self.state.update_player_laser();
-            self.state.update_aliens();
-            self.state.update_aliens_lasers();
-            self.state.apply_collisions();
+            let input = self.input.get_input(&self.key_bindings);
+            let takes_move = self.state.takes_move();
+            self.state.step(&input);
+            self.input.consume(&self.key_bindings, takes_move);
+
+            if let Some(recording) = self.recording.as_mut() {
+                recording.record_step(&input);
+            }
         }
 
         if self.get_elapsed_since_render().as_millis() > MS_PER_RENDER {
-            self.render()
-                .map_err(|err| format!("error while rendering game state, {err}"))?;
+            render_state(self.renderer.as_mut(), &self.state)
+                .map_err(LigmaError::terminal("error while rendering game state"))?;
 
-            self.last_render = SystemTime::now();
+            self.last_render = self.clock.now();
         }
 
         Ok(InputResult::Continue)
     }
 
-    fn render(&mut self) -> Result<()> {
-        let player_color = self.state.get_player_color();
+    fn set_last_update(&mut self) {
+        self.last_update = self.clock.now();
+    }
 
-        queue!(self.std_out, terminal::Clear(terminal::ClearType::All))?;
+    fn get_elapsed_since_update(&self) -> Duration {
+        self.clock.now().saturating_sub(self.last_update)
+    }
 
-        for Coord { x, y, ch } in &self.state.player.position {
-            queue!(
-                self.std_out,
-                cursor::MoveTo(*x, *y),
-                style::PrintStyledContent(ch.with(Color::Rgb {
-                    r: player_color.r,
-                    g: player_color.g,
-                    b: player_color.b,
-                }))
-            )?;
+    fn get_elapsed_since_render(&self) -> Duration {
+        self.clock.now().saturating_sub(self.last_render)
+    }
+
+    fn new_state(
+        assets: &Assets,
+        viewport: Viewport,
+        seed: Option<u64>,
+        config: &GameConfig,
+        difficulty: Difficulty,
+    ) -> State {
+        let seed = seed.unwrap_or_else(rand::random);
+
+        State::with_config(assets, viewport, seed, config.with_difficulty(difficulty))
+    }
+
+    fn resize(&mut self, width: u16, height: u16) {
+        self.viewport = Viewport::new(width, height);
+        self.renderer.resize(width, height);
+
+        if self.viewport.fits() {
+            self.state.resize(self.viewport);
+
+            if let Some(recording) = self.recording.as_mut() {
+                recording.record_resize(self.viewport);
+            }
         }
+    }
+
+    fn render_too_small_screen(&mut self) -> LigmaResult<()> {
+        self.render_too_small_for(Viewport::MIN_WIDTH, Viewport::MIN_HEIGHT)
+    }
 
-        let health_left = format!("HEALTH: {}", self.state.player.health);
+    fn render_too_small_for(&mut self, width: u16, height: u16) -> LigmaResult<()> {
+        let message = format!(
+            "TERMINAL TOO SMALL: {}x{}, NEED AT LEAST {width}x{height}",
+            self.viewport.get_width(),
+            self.viewport.get_height(),
+        );
 
-        queue!(
-            self.std_out,
-            cursor::MoveTo(2, VIEWPORT_MAX_Y - 1),
-            style::Print(health_left),
+        render_message(self.renderer.as_mut(), &self.viewport, &message)
+            .map_err(LigmaError::terminal("error while rendering"))
+    }
+
+    fn prepare_screen(&mut self) -> Result<()> {
+        enable_raw_mode()?;
+        execute!(
+            stdout(),
+            cursor::Hide,
+            terminal::EnterAlternateScreen,
+            event::EnableFocusChange
         )?;
 
-        if self.state.player.laser.is_some() {
-            for Coord { x, y, ch } in &self.state.player.laser.as_ref().unwrap().position {
-                queue!(
-                    self.std_out,
-                    cursor::MoveTo(*x, *y),
-                    style::PrintStyledContent(ch.with(Color::Rgb {
-                        r: player_color.r,
-                        g: player_color.g,
-                        b: player_color.b,
-                    }))
-                )?;
+        // Without press/release events the only way to tell a key is held is the OS key repeat,
+        // which moves the ship in bursts and stops it while another key is pressed.
+        if terminal::supports_keyboard_enhancement()? {
+            execute!(
+                stdout(),
+                event::PushKeyboardEnhancementFlags(
+                    KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
+                        | KeyboardEnhancementFlags::REPORT_EVENT_TYPES
+                )
+            )?;
+            self.input.set_reports_releases(true);
+        }
+
+        Ok(())
+    }
+
+    /// Waits up to one update for input, then drains every other pending event, so a burst of
+    /// key presses between frames is handled in the same frame.
+    fn poll_input(&mut self) -> Result<InputResult> {
+        let mut timeout = Duration::from_millis(MS_PER_UPDATE as u64);
+
+        while poll(timeout)? {
+            timeout = Duration::ZERO;
+
+            match self.handle_event(read()?) {
+                InputResult::Continue => (),
+                result => return Ok(result),
             }
         }
 
-        for aliens_row in &self.state.aliens.aliens_rows {
-            for alien in &aliens_row.aliens {
-                for Coord { x, y, ch } in &alien.position {
-                    queue!(self.std_out, cursor::MoveTo(*x, *y), style::Print(ch))?;
+        Ok(InputResult::Continue)
+    }
+
+    fn handle_event(&mut self, event: Event) -> InputResult {
+        match event {
+            Event::Key(
+                key @ KeyEvent {
+                    kind: KeyEventKind::Release,
+                    ..
+                },
+            ) => {
+                self.input.handle_key(&key);
+                InputResult::Continue
+            }
+            Event::Key(key) => match self.key_bindings.get_action(key.code) {
+                Some(Action::Quit) => InputResult::Quit,
+                Some(Action::Pause) => InputResult::Pause,
+                _ => {
+                    self.input.handle_key(&key);
+                    InputResult::Continue
                 }
+            },
+            Event::FocusLost => InputResult::Pause,
+            Event::Resize(width, height) => {
+                self.resize(width, height);
+                InputResult::Continue
             }
+            _ => InputResult::Continue,
         }
+    }
+
+    /// Shows the pause menu over the frozen game until an option is chosen. The simulation
+    /// doesn't advance while paused and picks up from the same tick on resume.
+    fn pause(&mut self) -> LigmaResult<InputResult> {
+        let mut selected = 0;
 
-        for laser in self.state.aliens.lasers.iter() {
-            for Coord { x, y, ch } in &laser.position {
-                queue!(self.std_out, cursor::MoveTo(*x, *y), style::Print(ch))?;
+        loop {
+            match self.viewport.fits() {
+                true => render_pause_menu(
+                    self.renderer.as_mut(),
+                    &self.state,
+                    &PauseOption::ALL.map(PauseOption::get_label),
+                    selected,
+                )
+                .map_err(LigmaError::terminal("error while rendering"))?,
+                false => self.render_too_small_screen()?,
             }
-        }
 
-        for bunker in &self.state.bunkers.positions {
-            for Coord { x, y, ch } in &bunker.position {
-                queue!(
-                    self.std_out,
-                    cursor::MoveTo(*x, *y),
-                    style::PrintStyledContent(ch.with(Color::Rgb {
-                        r: player_color.r,
-                        g: player_color.g,
-                        b: player_color.b,
-                    }))
-                )?;
+            match read().map_err(LigmaError::input("error while reading players input"))? {
+                Event::Resize(width, height) => self.resize(width, height),
+                Event::Key(KeyEvent {
+                    kind: KeyEventKind::Release,
+                    ..
+                }) => (),
+                Event::Key(KeyEvent { code, .. })
+                    if code == KeyCode::Up
+                        || self.key_bindings.is_bound(Action::MoveLeft, code) =>
+                {
+                    selected = selected.saturating_sub(1)
+                }
+                Event::Key(KeyEvent { code, .. })
+                    if code == KeyCode::Down
+                        || self.key_bindings.is_bound(Action::MoveRight, code) =>
+                {
+                    selected = (selected + 1).min(PauseOption::ALL.len() - 1)
+                }
+                Event::Key(KeyEvent { code, .. })
+                    if self.key_bindings.is_bound(Action::Pause, code) =>
+                {
+                    break
+                }
+                Event::Key(KeyEvent { code, .. })
+                    if self.key_bindings.is_bound(Action::Quit, code) =>
+                {
+                    return Ok(InputResult::Quit)
+                }
+                Event::Key(KeyEvent { code, .. })
+                    if self.key_bindings.is_bound(Action::Confirm, code) =>
+                {
+                    match PauseOption::ALL[selected] {
+                        PauseOption::Resume => break,
+                        PauseOption::Restart if self.viewport.fits() => {
+                            self.restart();
+                            return Ok(InputResult::Continue);
+                        }
+                        PauseOption::Restart => (),
+                        PauseOption::Controls => {
+                            if self.show_controls()? == InputResult::Quit {
+                                return Ok(InputResult::Quit);
+                            }
+                        }
+                        PauseOption::Quit => return Ok(InputResult::Quit),
+                    }
+                }
+                _ => (),
             }
         }
 
-        self.std_out.flush()
-    }
+        self.input.clear();
+        self.set_last_update();
 
-    fn set_last_update(&mut self) {
-        self.last_update = SystemTime::now();
+        Ok(InputResult::Continue)
     }
 
-    fn get_elapsed_since_update(&self) -> Duration {
-        self.last_update.elapsed().unwrap()
+    fn restart(&mut self) {
+        self.state = Self::new_state(
+            &self.assets,
+            self.viewport,
+            self.seed,
+            &self.config,
+            self.difficulty,
+        );
+        self.recording = self.record_path.as_ref().map(|_| {
+            Recording::new(
+                self.state.get_seed(),
+                self.difficulty,
+                self.viewport,
+                &self.assets,
+                self.config.with_difficulty(self.difficulty),
+            )
+        });
+        self.input.clear();
+        self.set_last_update();
     }
 
-    fn get_elapsed_since_render(&self) -> Duration {
-        self.last_render.elapsed().unwrap()
-    }
+    fn render_game_over_screen(&mut self, game_result: GameState) -> LigmaResult<InputResult> {
+        let confirm = self.key_bindings.get_label(Action::Confirm);
+        let quit = self.key_bindings.get_label(Action::Quit);
 
-    fn prepare_screen(&mut self) -> Result<()> {
-        enable_raw_mode()?;
-        execute!(self.std_out, cursor::Hide, terminal::EnterAlternateScreen)
-    }
+        let left = self.key_bindings.get_label(Action::MoveLeft);
+        let right = self.key_bindings.get_label(Action::MoveRight);
+
+        let message = match game_result {
+            GameState::NewGame => format!("PRESS {confirm} TO START THE GAME. {quit} TO QUIT"),
+            GameState::Lost => format!(
+                "YOU LOST. SCORE: {}. PRESS {confirm} TO PLAY AGAIN. {quit} TO QUIT",
+                self.state.get_score()
+            ),
+        };
+        let warnings = self
+            .warnings
+            .drain(..)
+            .map(|warning| format!("WARNING: {warning}"))
+            .collect::<Vec<_>>();
 
-    fn handle_user_input(&mut self) -> Result<InputResult> {
-        match read()? {
-            Event::Key(KeyEvent {
-                code: KeyCode::Left,
-                ..
-            }) => {
-                self.state.player_go_left();
-                Ok(InputResult::Continue)
+        loop {
+            let mut lines = vec![
+                message.clone(),
+                String::new(),
+                format!("DIFFICULTY: < {} >", self.difficulty.get_label()),
+                format!("{left} / {right} TO CHANGE"),
+                String::new(),
+            ];
+            lines.extend(self.get_high_score_lines());
+            lines.extend(warnings.clone());
+
+            self.render_screen(&lines)?;
+
+            match read().map_err(LigmaError::input("error while reading players input"))? {
+                Event::Resize(width, height) => self.resize(width, height),
+                Event::Key(KeyEvent {
+                    kind: KeyEventKind::Release,
+                    ..
+                }) => (),
+                Event::Key(KeyEvent { code, .. })
+                    if self.key_bindings.is_bound(Action::MoveLeft, code) =>
+                {
+                    self.difficulty = self.difficulty.previous();
+                }
+                Event::Key(KeyEvent { code, .. })
+                    if self.key_bindings.is_bound(Action::MoveRight, code) =>
+                {
+                    self.difficulty = self.difficulty.next();
+                }
+                Event::Key(KeyEvent { code, .. })
+                    if self.key_bindings.is_bound(Action::Quit, code) =>
+                {
+                    return Ok(InputResult::Quit)
+                }
+                Event::Key(KeyEvent { code, .. })
+                    if self.key_bindings.is_bound(Action::Confirm, code)
+                        && self.viewport.fits() =>
+                {
+                    self.restart();
+                    return Ok(InputResult::Continue);
+                }
+                _ => (),
             }
-            Event::Key(KeyEvent {
-                code: KeyCode::Right,
-                ..
-            }) => {
-                self.state.player_go_right();
-                Ok(InputResult::Continue)
+        }
+    }
+
+    /// Lists the active key bindings until any key is pressed.
+    fn show_controls(&mut self) -> LigmaResult<InputResult> {
+        let mut lines = vec!["CONTROLS".to_string(), String::new()];
+        lines.extend(Action::ALL.map(|action| {
+            format!(
+                "{:<10}  {:<20}",
+                action.get_label(),
+                self.key_bindings.get_label(action)
+            )
+        }));
+        lines.extend([String::new(), "PRESS ANY KEY TO GO BACK".to_string()]);
+
+        loop {
+            self.render_screen(&lines)?;
+
+            match read().map_err(LigmaError::input("error while reading players input"))? {
+                Event::Resize(width, height) => self.resize(width, height),
+                Event::Key(KeyEvent {
+                    kind: KeyEventKind::Release,
+                    ..
+                }) => (),
+                Event::Key(_) => return Ok(InputResult::Continue),
+                _ => (),
             }
-            Event::Key(KeyEvent {
-                code: KeyCode::Up, ..
-            }) => Ok(InputResult::Continue),
-            Event::Key(KeyEvent {
-                code: KeyCode::Char(ch),
-                ..
-            }) => match ch {
-                'q' => Ok(InputResult::Quit),
-                ' ' => {
-                    self.state.player_shoot();
-                    Ok(InputResult::Continue)
-                }
-                _ => Ok(InputResult::Continue),
-            },
-            _ => Ok(InputResult::Continue),
         }
     }
 
-    fn render_game_over_screen(&mut self, game_result: GameState) -> LigmaResult<InputResult> {
-        let message = match game_result {
-            GameState::NewGame => "PRESS 'ENTER' TO START THE GAME. 'Q' TO QUIT",
-            GameState::Won => "CONGRATS! YOU WON. PRESS 'ENTER' TO PLAY AGAIN. 'Q' TO QUIT",
-            GameState::Lost => "YOU LOST. PRESS 'ENTER' TO PLAY AGAIN. 'Q' TO QUIT",
-        };
+    /// Asks for a name when the final score makes it into the high scores, then saves the table.
+    /// A failed save doesn't end the game, it is reported on the next screen instead.
+    fn record_high_score(&mut self) -> LigmaResult<InputResult> {
+        let score = self.state.get_score();
 
-        execute!(
-            self.std_out,
-            terminal::Clear(terminal::ClearType::All,),
-            cursor::MoveTo(VIEWPORT_MAX_X / 2 + 10, VIEWPORT_MAX_Y / 2),
-            style::Print(message),
-        )
-        .map_err(|err| format!("error while rendering, {err}"))?;
+        if !self.keeps_high_scores() || !self.high_scores.qualifies(score, self.difficulty) {
+            return Ok(InputResult::Continue);
+        }
+
+        let mut name = String::new();
+        let save_keys = self.get_name_entry_keys(Action::Confirm, KeyCode::Enter);
+        let skip_keys = self.get_name_entry_keys(Action::Pause, KeyCode::Esc);
+        let prompt = format!(
+            "PRESS {} TO SAVE. {} TO SKIP",
+            get_keys_label(&save_keys),
+            get_keys_label(&skip_keys)
+        );
 
         loop {
-            match read().map_err(|err| format!("error while reading players input, {err}"))? {
+            let lines = [
+                format!("NEW HIGH SCORE: {score}"),
+                String::new(),
+                format!("ENTER YOUR NAME: {name}_"),
+                String::new(),
+                prompt.clone(),
+            ];
+
+            self.render_screen(&lines)?;
+
+            match read().map_err(LigmaError::input("error while reading players input"))? {
+                Event::Resize(width, height) => self.resize(width, height),
                 Event::Key(KeyEvent {
-                    code: KeyCode::Char(ch),
+                    kind: KeyEventKind::Release,
                     ..
-                }) => match ch {
-                    'q' => return Ok(InputResult::Quit),
-                    _ => (),
-                },
+                }) => (),
+                Event::Key(KeyEvent { code, .. }) if skip_keys.contains(&code) => {
+                    return Ok(InputResult::Continue)
+                }
+                Event::Key(KeyEvent { code, .. })
+                    if save_keys.contains(&code) && !name.trim().is_empty() =>
+                {
+                    break
+                }
                 Event::Key(KeyEvent {
-                    code: KeyCode::Enter,
+                    code: KeyCode::Backspace,
                     ..
                 }) => {
-                    self.state = State::new()?;
-                    return Ok(InputResult::Continue);
+                    name.pop();
+                }
+                Event::Key(KeyEvent {
+                    code: KeyCode::Char(ch),
+                    ..
+                }) if is_name_char(ch) && name.len() < HighScores::MAX_NAME_LENGTH => {
+                    name.push(ch.to_ascii_uppercase());
                 }
                 _ => (),
             }
         }
+
+        self.high_scores.insert(HighScore {
+            name: name.trim().to_string(),
+            score,
+            wave: self.state.get_wave(),
+            difficulty: self.difficulty,
+        });
+
+        if let Err(err) = self.high_scores.save() {
+            self.warnings.push(err.to_string());
+        }
+
+        Ok(InputResult::Continue)
+    }
+
+    /// Keys bound to `action` that can't be part of a name, or `fallback` if every bound key can.
+    fn get_name_entry_keys(&self, action: Action, fallback: KeyCode) -> Vec<KeyCode> {
+        let keys = self
+            .key_bindings
+            .get_keys(action)
+            .iter()
+            .copied()
+            .filter(|&code| match code {
+                KeyCode::Char(ch) => !is_name_char(ch),
+                code => code != KeyCode::Backspace,
+            })
+            .collect::<Vec<_>>();
+
+        match keys.is_empty() {
+            true => vec![fallback],
+            false => keys,
+        }
+    }
+
+    fn keeps_high_scores(&self) -> bool {
+        self.config == GameConfig::default()
     }
+
+    fn get_high_score_lines(&self) -> Vec<String> {
+        if !self.keeps_high_scores() {
+            return vec!["HIGH SCORES ARE NOT KEPT WITH A CUSTOM CONFIG".to_string()];
+        }
+
+        let entries = self.high_scores.get_entries(self.difficulty);
+
+        if entries.is_empty() {
+            return vec!["NO HIGH SCORES YET".to_string()];
+        }
+
+        let mut lines = vec!["HIGH SCORES".to_string(), String::new()];
+
+        lines.extend(entries.iter().enumerate().map(|(idx, entry)| {
+            format!(
+                "{:>2}. {:<width$} {:>7}  WAVE {:>2}",
+                idx + 1,
+                entry.name,
+                entry.score,
+                entry.wave,
+                width = HighScores::MAX_NAME_LENGTH,
+            )
+        }));
+
+        lines
+    }
+
+    fn render_screen(&mut self, lines: &[String]) -> LigmaResult<()> {
+        match self.viewport.fits() {
+            true => render_lines(self.renderer.as_mut(), &self.viewport, lines)
+                .map_err(LigmaError::terminal("error while rendering")),
+            false => self.render_too_small_screen(),
+        }
+    }
+}
+
+impl PauseOption {
+    const ALL: [PauseOption; 4] = [
+        PauseOption::Resume,
+        PauseOption::Restart,
+        PauseOption::Controls,
+        PauseOption::Quit,
+    ];
+
+    fn get_label(self) -> &'static str {
+        match self {
+            PauseOption::Resume => "RESUME",
+            PauseOption::Restart => "RESTART",
+            PauseOption::Controls => "CONTROLS",
+            PauseOption::Quit => "QUIT",
+        }
+    }
+}
+
+fn is_name_char(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || ch == ' '
 }
@@ -0,0 +1,323 @@
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    config::Difficulty,
+    ligma_result::{LigmaError, LigmaResult},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HighScore {
+    pub name: String,
+    pub score: u32,
+    pub wave: u32,
+    pub difficulty: Difficulty,
+}
+
+/// Best scores, highest first, kept separately for every difficulty. Without a path the table only
+/// lives for the current session.
+#[derive(Debug, Clone, Default)]
+pub struct HighScores {
+    entries: Vec<HighScore>,
+    path: Option<PathBuf>,
+    /// The file at `path` couldn't be read and is moved aside on the next save.
+    corrupted: bool,
+}
+
+impl HighScores {
+    pub const MAX_ENTRIES: usize = 10;
+    pub const MAX_NAME_LENGTH: usize = 10;
+    const HEADER: &'static str = "ligma-high-scores";
+    /// Version 1 files have no difficulty column, their scores were all played on normal.
+    const VERSION: u32 = 2;
+
+    /// `high_scores.txt` in the user's data directory, if there is one.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("ligma-invaders").join("high_scores.txt"))
+    }
+
+    pub fn empty(path: Option<PathBuf>) -> HighScores {
+        HighScores {
+            entries: vec![],
+            path,
+            corrupted: false,
+        }
+    }
+
+    /// An empty table replacing a file that failed to load. The file is kept next to the new one
+    /// under `get_backup_path` instead of being overwritten.
+    pub fn corrupted(path: Option<PathBuf>) -> HighScores {
+        HighScores {
+            corrupted: path.is_some(),
+            ..Self::empty(path)
+        }
+    }
+
+    pub fn get_backup_path(&self) -> Option<PathBuf> {
+        self.path.as_ref().map(|path| {
+            let mut backup = path.clone().into_os_string();
+            backup.push(".corrupt");
+            PathBuf::from(backup)
+        })
+    }
+
+    /// Reads the table from `path`. A missing file is an empty table.
+    pub fn load(path: Option<PathBuf>) -> LigmaResult<HighScores> {
+        let Some(file) = &path else {
+            return Ok(Self::empty(None));
+        };
+
+        let content = match fs::read_to_string(file) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Self::empty(path)),
+            Err(source) => {
+                return Err(LigmaError::HighScoresLoading {
+                    file: file.display().to_string(),
+                    source,
+                })
+            }
+        };
+
+        let mut high_scores = HighScores {
+            entries: parse_high_scores(file, &content)?,
+            path,
+            corrupted: false,
+        };
+
+        high_scores
+            .entries
+            .sort_by_key(|e| std::cmp::Reverse(e.score));
+        high_scores.truncate();
+
+        Ok(high_scores)
+    }
+
+    pub fn get_entries(&self, difficulty: Difficulty) -> Vec<&HighScore> {
+        self.entries
+            .iter()
+            .filter(|e| e.difficulty == difficulty)
+            .collect()
+    }
+
+    pub fn qualifies(&self, score: u32, difficulty: Difficulty) -> bool {
+        let entries = self.get_entries(difficulty);
+
+        score > 0
+            && (entries.len() < Self::MAX_ENTRIES
+                || entries.last().is_some_and(|e| score > e.score))
+    }
+
+    /// Inserts the entry below any equal scores and drops whatever falls off its table.
+    pub fn insert(&mut self, entry: HighScore) {
+        let idx = self
+            .entries
+            .iter()
+            .position(|e| e.score < entry.score)
+            .unwrap_or(self.entries.len());
+
+        self.entries.insert(idx, entry);
+        self.truncate();
+    }
+
+    /// Keeps the best `MAX_ENTRIES` of every difficulty.
+    fn truncate(&mut self) {
+        let mut counts = HashMap::new();
+
+        self.entries.retain(|e| {
+            let count = counts.entry(e.difficulty).or_insert(0);
+            *count += 1;
+            *count <= Self::MAX_ENTRIES
+        });
+    }
+
+    /// Writes the table to a temporary file first, so a failed write never leaves a truncated
+    /// table behind.
+    pub fn save(&mut self) -> LigmaResult<()> {
+        let backup = self.get_backup_path();
+
+        let Some(file) = &self.path else {
+            return Ok(());
+        };
+
+        let error = |source| LigmaError::HighScoresSaving {
+            file: file.display().to_string(),
+            source,
+        };
+
+        if let (true, Some(backup)) = (self.corrupted, backup) {
+            match fs::rename(file, backup) {
+                Ok(()) => (),
+                Err(err) if err.kind() == io::ErrorKind::NotFound => (),
+                Err(err) => return Err(error(err)),
+            }
+
+            self.corrupted = false;
+        }
+
+        let mut content = format!("{} {}\n", Self::HEADER, Self::VERSION);
+
+        for entry in &self.entries {
+            content.push_str(&format!(
+                "{} {} {} {}\n",
+                entry.score,
+                entry.wave,
+                entry.difficulty.get_name(),
+                entry.name
+            ));
+        }
+
+        if let Some(dir) = file.parent() {
+            fs::create_dir_all(dir).map_err(error)?;
+        }
+
+        let temporary = file.with_extension("tmp");
+        fs::write(&temporary, content).map_err(error)?;
+        fs::rename(&temporary, file).map_err(error)
+    }
+}
+
+fn parse_high_scores(file: &Path, content: &str) -> LigmaResult<Vec<HighScore>> {
+    let error = |line: usize, text: &str| LigmaError::HighScoresParsing {
+        file: file.display().to_string(),
+        line,
+        text: text.to_string(),
+    };
+
+    let mut lines = content.lines();
+    let header = lines.next().unwrap_or_default();
+
+    let version = header
+        .strip_prefix(HighScores::HEADER)
+        .and_then(|version| version.strip_prefix(' '))
+        .and_then(|version| version.parse::<u32>().ok())
+        .filter(|version| (1..=HighScores::VERSION).contains(version))
+        .ok_or_else(|| error(1, header))?;
+
+    lines
+        .enumerate()
+        .map(|(idx, line)| {
+            let mut parts = line.splitn(if version == 1 { 3 } else { 4 }, ' ');
+            let mut next_number = || parts.next().and_then(|part| part.parse::<u32>().ok());
+
+            let (Some(score), Some(wave)) = (next_number(), next_number()) else {
+                return Err(error(idx + 2, line));
+            };
+
+            let difficulty = match version {
+                1 => Difficulty::Normal,
+                _ => parts
+                    .next()
+                    .and_then(Difficulty::from_name)
+                    .ok_or_else(|| error(idx + 2, line))?,
+            };
+
+            let name = parts.next().unwrap_or_default().to_string();
+
+            if name.is_empty() || name.chars().count() > HighScores::MAX_NAME_LENGTH {
+                return Err(error(idx + 2, line));
+            }
+
+            Ok(HighScore {
+                name,
+                score,
+                wave,
+                difficulty,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh directory for one test, so that tests running in parallel don't share files.
+    fn get_test_path(test: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("ligma-high-scores-{}-{test}", std::process::id()));
+
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        dir.join("high_scores.txt")
+    }
+
+    fn get_entry(name: &str, score: u32, wave: u32, difficulty: Difficulty) -> HighScore {
+        HighScore {
+            name: name.to_string(),
+            score,
+            wave,
+            difficulty,
+        }
+    }
+
+    #[test]
+    fn version_1_scores_are_upgraded_as_normal() {
+        let path = get_test_path("upgrade");
+        fs::write(&path, "ligma-high-scores 1\n120 2 ann\n340 3 bob smith\n").unwrap();
+
+        let mut high_scores = HighScores::load(Some(path.clone())).unwrap();
+
+        assert_eq!(
+            high_scores.get_entries(Difficulty::Normal),
+            [
+                &get_entry("bob smith", 340, 3, Difficulty::Normal),
+                &get_entry("ann", 120, 2, Difficulty::Normal),
+            ]
+        );
+
+        high_scores.save().unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with("ligma-high-scores 2\n"));
+        assert_eq!(
+            HighScores::load(Some(path)).unwrap().entries,
+            high_scores.entries
+        );
+    }
+
+    #[test]
+    fn truncated_or_garbage_files_fail_to_load() {
+        let path = get_test_path("garbage");
+
+        for content in [
+            "ligma-high",
+            "ligma-high-scores 3\n",
+            "ligma-high-scores 2\n120 2 normal\n",
+            "ligma-high-scores 2\n120 2 normal ann\n12",
+            "\u{0}\u{1}garbage",
+        ] {
+            fs::write(&path, content).unwrap();
+
+            assert!(
+                matches!(
+                    HighScores::load(Some(path.clone())),
+                    Err(LigmaError::HighScoresParsing { .. })
+                ),
+                "{content:?} loaded"
+            );
+        }
+    }
+
+    #[test]
+    fn saving_over_a_corrupted_file_moves_it_aside() {
+        let path = get_test_path("corrupt");
+        fs::write(&path, "garbage").unwrap();
+
+        let mut high_scores = HighScores::corrupted(Some(path.clone()));
+        high_scores.insert(get_entry("ann", 120, 4, Difficulty::Hard));
+        high_scores.save().unwrap();
+
+        let backup = high_scores.get_backup_path().unwrap();
+        assert_eq!(fs::read_to_string(backup).unwrap(), "garbage");
+        assert_eq!(
+            HighScores::load(Some(path))
+                .unwrap()
+                .get_entries(Difficulty::Hard),
+            [&get_entry("ann", 120, 4, Difficulty::Hard)]
+        );
+    }
+}
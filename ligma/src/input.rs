@@ -0,0 +1,85 @@
+use std::collections::HashSet;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
+
+use crate::{
+    key_bindings::{Action, KeyBindings},
+    state::Input,
+};
+
+/// Keys collected from the terminal between simulation ticks: the ones currently held down and
+/// the ones pressed since the last tick.
+#[derive(Debug, Default)]
+pub struct InputState {
+    held: HashSet<KeyCode>,
+    pressed: HashSet<KeyCode>,
+    reports_releases: bool,
+}
+
+impl InputState {
+    pub fn handle_key(&mut self, event: &KeyEvent) {
+        match event.kind {
+            KeyEventKind::Press | KeyEventKind::Repeat => {
+                self.held.insert(event.code);
+                self.pressed.insert(event.code);
+            }
+            KeyEventKind::Release => {
+                self.held.remove(&event.code);
+            }
+        }
+    }
+
+    pub fn reports_releases(&self) -> bool {
+        self.reports_releases
+    }
+
+    /// Set once the terminal is known to send release events. Until then keys are only held for
+    /// the tick they were pressed in.
+    pub fn set_reports_releases(&mut self, reports_releases: bool) {
+        self.reports_releases = reports_releases;
+    }
+
+    pub fn is_held(&self, code: KeyCode) -> bool {
+        self.held.contains(&code)
+    }
+
+    pub fn was_pressed(&self, code: KeyCode) -> bool {
+        self.pressed.contains(&code)
+    }
+
+    /// Input for the next tick. A key tapped and released between two ticks still counts.
+    pub fn get_input(&self, bindings: &KeyBindings) -> Input {
+        let is_down = |action| {
+            bindings
+                .get_keys(action)
+                .iter()
+                .any(|&code| self.is_held(code) || self.was_pressed(code))
+        };
+
+        Input {
+            left: is_down(Action::MoveLeft),
+            right: is_down(Action::MoveRight),
+            fire: is_down(Action::Fire),
+        }
+    }
+
+    /// Marks the input as consumed by a tick. Without release events a key only counts as held
+    /// until then. Presses of the move keys stay pending until a tick that `took_move`, so taps
+    /// between two moves of the ship aren't lost.
+    pub fn consume(&mut self, bindings: &KeyBindings, took_move: bool) {
+        self.pressed.retain(|&code| {
+            !took_move
+                && (bindings.is_bound(Action::MoveLeft, code)
+                    || bindings.is_bound(Action::MoveRight, code))
+        });
+
+        if !self.reports_releases {
+            self.held.clear();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.held.clear();
+        self.pressed.clear();
+    }
+}
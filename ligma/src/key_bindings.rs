@@ -0,0 +1,229 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crossterm::event::KeyCode;
+
+use crate::ligma_result::{LigmaError, LigmaResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    Fire,
+    Pause,
+    Quit,
+    Confirm,
+}
+
+/// Keys bound to each action. A key triggers at most one action.
+#[derive(Debug, Clone)]
+pub struct KeyBindings {
+    keys: Vec<(Action, Vec<KeyCode>)>,
+}
+
+impl Action {
+    pub const ALL: [Action; 6] = [
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Fire,
+        Action::Pause,
+        Action::Quit,
+        Action::Confirm,
+    ];
+
+    /// Name used in the key bindings file.
+    pub fn get_name(self) -> &'static str {
+        match self {
+            Action::MoveLeft => "move_left",
+            Action::MoveRight => "move_right",
+            Action::Fire => "fire",
+            Action::Pause => "pause",
+            Action::Quit => "quit",
+            Action::Confirm => "confirm",
+        }
+    }
+
+    pub fn get_label(self) -> &'static str {
+        match self {
+            Action::MoveLeft => "MOVE LEFT",
+            Action::MoveRight => "MOVE RIGHT",
+            Action::Fire => "FIRE",
+            Action::Pause => "PAUSE",
+            Action::Quit => "QUIT",
+            Action::Confirm => "CONFIRM",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Action> {
+        Self::ALL
+            .into_iter()
+            .find(|action| action.get_name() == name)
+    }
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings {
+            keys: vec![
+                (Action::MoveLeft, vec![KeyCode::Left]),
+                (Action::MoveRight, vec![KeyCode::Right]),
+                (Action::Fire, vec![KeyCode::Char(' ')]),
+                (Action::Pause, vec![KeyCode::Char('p'), KeyCode::Esc]),
+                (Action::Quit, vec![KeyCode::Char('q')]),
+                (Action::Confirm, vec![KeyCode::Enter]),
+            ],
+        }
+    }
+}
+
+impl KeyBindings {
+    /// `keys.txt` in the user's config directory, if there is one.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("ligma-invaders").join("keys.txt"))
+    }
+
+    /// Reads bindings from `file`, one `action key [key...]` line per action. Blank lines and lines
+    /// starting with `#` are skipped. Actions missing from the file, or the whole file if it
+    /// doesn't exist, keep their default keys.
+    pub fn load(file: &Path) -> LigmaResult<KeyBindings> {
+        let content = match fs::read_to_string(file) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(source) => {
+                return Err(LigmaError::KeyBindingsLoading {
+                    file: file.display().to_string(),
+                    source,
+                })
+            }
+        };
+
+        let mut bindings = Self::default();
+
+        for (idx, line) in content.lines().enumerate() {
+            let error = |message: String| LigmaError::KeyBindingsParsing {
+                file: file.display().to_string(),
+                line: idx + 1,
+                text: line.to_string(),
+                message,
+            };
+
+            let mut parts = line.split_whitespace();
+
+            let Some(name) = parts.next().filter(|part| !part.starts_with('#')) else {
+                continue;
+            };
+
+            let action =
+                Action::from_name(name).ok_or_else(|| error(format!("unknown action '{name}'")))?;
+            let keys = parts
+                .map(|key| parse_key(key).ok_or_else(|| error(format!("unknown key '{key}'"))))
+                .collect::<LigmaResult<Vec<_>>>()?;
+
+            if keys.is_empty() {
+                return Err(error(format!("no keys bound to '{name}'")));
+            }
+
+            bindings.set_keys(action, keys);
+        }
+
+        bindings.check_conflicts(file)?;
+
+        Ok(bindings)
+    }
+
+    pub fn get_keys(&self, action: Action) -> &[KeyCode] {
+        self.keys
+            .iter()
+            .find(|(a, _)| *a == action)
+            .map(|(_, keys)| keys.as_slice())
+            .unwrap_or_default()
+    }
+
+    pub fn get_action(&self, code: KeyCode) -> Option<Action> {
+        self.keys
+            .iter()
+            .find(|(_, keys)| keys.contains(&code))
+            .map(|(action, _)| *action)
+    }
+
+    pub fn is_bound(&self, action: Action, code: KeyCode) -> bool {
+        self.get_keys(action).contains(&code)
+    }
+
+    /// Keys bound to the action as shown on screen, e.g. `'P' / 'ESC'`.
+    pub fn get_label(&self, action: Action) -> String {
+        get_keys_label(self.get_keys(action))
+    }
+
+    fn set_keys(&mut self, action: Action, keys: Vec<KeyCode>) {
+        if let Some((_, bound)) = self.keys.iter_mut().find(|(a, _)| *a == action) {
+            *bound = keys;
+        }
+    }
+
+    fn check_conflicts(&self, file: &Path) -> LigmaResult<()> {
+        for (idx, (first, keys)) in self.keys.iter().enumerate() {
+            for (second, other_keys) in &self.keys[idx + 1..] {
+                if let Some(&code) = keys.iter().find(|code| other_keys.contains(code)) {
+                    return Err(LigmaError::KeyBindingsConflict {
+                        file: file.display().to_string(),
+                        key: get_key_name(code),
+                        first: first.get_name(),
+                        second: second.get_name(),
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Keys as shown on screen, e.g. `'P' / 'ESC'`.
+pub fn get_keys_label(keys: &[KeyCode]) -> String {
+    keys.iter()
+        .map(|&code| format!("'{}'", get_key_name(code).to_ascii_uppercase()))
+        .collect::<Vec<_>>()
+        .join(" / ")
+}
+
+fn parse_key(key: &str) -> Option<KeyCode> {
+    let mut chars = key.chars();
+
+    if let (Some(ch), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(ch));
+    }
+
+    let code = match key.to_ascii_lowercase().as_str() {
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "space" => KeyCode::Char(' '),
+        "enter" => KeyCode::Enter,
+        "esc" => KeyCode::Esc,
+        "tab" => KeyCode::Tab,
+        "backspace" => KeyCode::Backspace,
+        _ => return None,
+    };
+
+    Some(code)
+}
+
+fn get_key_name(code: KeyCode) -> String {
+    match code {
+        KeyCode::Left => "left".to_string(),
+        KeyCode::Right => "right".to_string(),
+        KeyCode::Up => "up".to_string(),
+        KeyCode::Down => "down".to_string(),
+        KeyCode::Char(' ') => "space".to_string(),
+        KeyCode::Enter => "enter".to_string(),
+        KeyCode::Esc => "esc".to_string(),
+        KeyCode::Tab => "tab".to_string(),
+        KeyCode::Backspace => "backspace".to_string(),
+        KeyCode::Char(ch) => ch.to_string(),
+        code => format!("{code:?}").to_ascii_lowercase(),
+    }
+}
@@ -1,3 +1,17 @@
+pub mod assets;
+pub mod clock;
+pub mod config;
+pub mod effects;
+pub mod formation;
+pub mod frame_buffer;
 pub mod game;
+pub mod high_scores;
+pub mod input;
+pub mod key_bindings;
 pub mod ligma_result;
-mod state;
+pub mod recording;
+pub mod renderer;
+pub mod scheduler;
+pub mod sprite;
+pub mod state;
+pub mod viewport;
@@ -1 +1,263 @@
-pub type LigmaResult<T> = Result<T, String>;
+use std::{error::Error, fmt, io, num::ParseIntError};
+
+pub type LigmaResult<T> = Result<T, LigmaError>;
+
+#[derive(Debug)]
+pub enum LigmaError {
+    Terminal {
+        context: &'static str,
+        source: io::Error,
+    },
+    Input {
+        context: &'static str,
+        source: io::Error,
+    },
+    AssetLoading {
+        file: String,
+        source: io::Error,
+    },
+    /// `line` is 1-based, 0 when the error concerns the file as a whole.
+    AssetParsing {
+        file: String,
+        line: usize,
+        text: String,
+        kind: AssetErrorKind,
+    },
+    Config {
+        field: String,
+        message: String,
+    },
+    ConfigLoading {
+        file: String,
+        source: io::Error,
+    },
+    ConfigParsing {
+        file: String,
+        source: toml::de::Error,
+    },
+    HighScoresLoading {
+        file: String,
+        source: io::Error,
+    },
+    HighScoresSaving {
+        file: String,
+        source: io::Error,
+    },
+    /// `line` is 1-based.
+    HighScoresParsing {
+        file: String,
+        line: usize,
+        text: String,
+    },
+    RecordingLoading {
+        file: String,
+        source: io::Error,
+    },
+    RecordingSaving {
+        file: String,
+        source: io::Error,
+    },
+    RecordingParsing {
+        file: String,
+        message: String,
+    },
+    RecordingAssetsMismatch,
+    KeyBindingsLoading {
+        file: String,
+        source: io::Error,
+    },
+    /// `line` is 1-based.
+    KeyBindingsParsing {
+        file: String,
+        line: usize,
+        text: String,
+        message: String,
+    },
+    KeyBindingsConflict {
+        file: String,
+        key: String,
+        first: &'static str,
+        second: &'static str,
+    },
+}
+
+#[derive(Debug)]
+pub enum AssetErrorKind {
+    MissingCoordinate,
+    InvalidCoordinate(ParseIntError),
+    TooFewCells { min: usize, found: usize },
+    CellOutOfBounds { width: u16, height: u16 },
+    InvalidFrameDuration,
+    InvalidStyle(String),
+    UnknownFormationDirective(String),
+    InvalidFormationValue(String),
+    UnknownSpecies(String),
+    MissingFormationDirective(&'static str),
+    FormationTooTall { bottom: u32, limit: u16 },
+}
+
+impl LigmaError {
+    pub fn terminal(context: &'static str) -> impl FnOnce(io::Error) -> LigmaError {
+        move |source| LigmaError::Terminal { context, source }
+    }
+
+    pub fn input(context: &'static str) -> impl FnOnce(io::Error) -> LigmaError {
+        move |source| LigmaError::Input { context, source }
+    }
+
+    pub fn config(field: impl Into<String>, message: impl Into<String>) -> LigmaError {
+        LigmaError::Config {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for LigmaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LigmaError::Terminal { context, .. } | LigmaError::Input { context, .. } => {
+                write!(f, "{context}")
+            }
+            LigmaError::AssetLoading { file, .. } => write!(f, "error loading asset '{file}'"),
+            LigmaError::AssetParsing { file, line: 0, .. } => {
+                write!(f, "error parsing asset '{file}'")
+            }
+            LigmaError::AssetParsing {
+                file, line, text, ..
+            } => write!(f, "error parsing asset '{file}' at line {line}: '{text}'"),
+            LigmaError::Config { field, message } => {
+                write!(f, "invalid configuration for '{field}': {message}")
+            }
+            LigmaError::ConfigLoading { file, .. } => write!(f, "error loading config '{file}'"),
+            LigmaError::ConfigParsing { file, .. } => write!(f, "error parsing config '{file}'"),
+            LigmaError::HighScoresLoading { file, .. } => {
+                write!(f, "error loading high scores '{file}'")
+            }
+            LigmaError::HighScoresSaving { file, .. } => {
+                write!(f, "error saving high scores '{file}'")
+            }
+            LigmaError::HighScoresParsing { file, line, text } => write!(
+                f,
+                "high scores '{file}' are corrupted at line {line}: '{text}'"
+            ),
+            LigmaError::RecordingLoading { file, .. } => {
+                write!(f, "error loading recording '{file}'")
+            }
+            LigmaError::RecordingSaving { file, .. } => {
+                write!(f, "error saving recording '{file}'")
+            }
+            LigmaError::RecordingParsing { file, message } => {
+                write!(f, "error parsing recording '{file}': {message}")
+            }
+            LigmaError::RecordingAssetsMismatch => write!(
+                f,
+                "the recording was played with different assets, replay it with the same asset files"
+            ),
+            LigmaError::KeyBindingsLoading { file, .. } => {
+                write!(f, "error loading key bindings '{file}'")
+            }
+            LigmaError::KeyBindingsParsing {
+                file,
+                line,
+                text,
+                message,
+            } => write!(
+                f,
+                "error parsing key bindings '{file}' at line {line}: '{text}', {message}"
+            ),
+            LigmaError::KeyBindingsConflict {
+                file,
+                key,
+                first,
+                second,
+            } => write!(
+                f,
+                "key bindings '{file}' bind '{key}' to both '{first}' and '{second}'"
+            ),
+        }
+    }
+}
+
+impl Error for LigmaError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LigmaError::Terminal { source, .. }
+            | LigmaError::Input { source, .. }
+            | LigmaError::AssetLoading { source, .. }
+            | LigmaError::HighScoresLoading { source, .. }
+            | LigmaError::HighScoresSaving { source, .. }
+            | LigmaError::RecordingLoading { source, .. }
+            | LigmaError::RecordingSaving { source, .. }
+            | LigmaError::KeyBindingsLoading { source, .. }
+            | LigmaError::ConfigLoading { source, .. } => Some(source),
+            LigmaError::ConfigParsing { source, .. } => Some(source),
+            LigmaError::AssetParsing { kind, .. } => Some(kind),
+            LigmaError::Config { .. }
+            | LigmaError::HighScoresParsing { .. }
+            | LigmaError::RecordingParsing { .. }
+            | LigmaError::RecordingAssetsMismatch
+            | LigmaError::KeyBindingsParsing { .. }
+            | LigmaError::KeyBindingsConflict { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for AssetErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AssetErrorKind::MissingCoordinate => write!(f, "expected 'x y [char] [style...]'"),
+            AssetErrorKind::InvalidCoordinate(_) => write!(f, "coordinate is not a valid u16"),
+            AssetErrorKind::TooFewCells { min, found } => {
+                write!(f, "expected at least {min} cell(s), found {found}")
+            }
+            AssetErrorKind::CellOutOfBounds { width, height } => write!(
+                f,
+                "cell lies outside {width}x{height}, the size of the smallest screen"
+            ),
+            AssetErrorKind::InvalidFrameDuration => {
+                write!(f, "frame duration is not a positive integer")
+            }
+            AssetErrorKind::InvalidStyle(style) => write!(
+                f,
+                "invalid style '{style}', expected fg=#rrggbb, bg=#rrggbb or an attribute"
+            ),
+            AssetErrorKind::UnknownFormationDirective(directive) => write!(
+                f,
+                "unknown directive '{directive}', expected columns, spacing, row_spacing, offset, stagger or row"
+            ),
+            AssetErrorKind::InvalidFormationValue(directive) => {
+                write!(f, "invalid value for '{directive}'")
+            }
+            AssetErrorKind::UnknownSpecies(species) => write!(
+                f,
+                "unknown species '{species}', expected squid, crab or octopus"
+            ),
+            AssetErrorKind::MissingFormationDirective(directive) => {
+                write!(f, "missing '{directive}'")
+            }
+            AssetErrorKind::FormationTooTall { bottom, limit } => write!(
+                f,
+                "formation takes {bottom} rows from the top of the screen, at most {limit} fit above the bunkers on the smallest screen"
+            ),
+        }
+    }
+}
+
+impl Error for AssetErrorKind {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            AssetErrorKind::InvalidCoordinate(err) => Some(err),
+            AssetErrorKind::MissingCoordinate
+            | AssetErrorKind::TooFewCells { .. }
+            | AssetErrorKind::CellOutOfBounds { .. }
+            | AssetErrorKind::InvalidFrameDuration
+            | AssetErrorKind::InvalidStyle(_)
+            | AssetErrorKind::UnknownFormationDirective(_)
+            | AssetErrorKind::InvalidFormationValue(_)
+            | AssetErrorKind::UnknownSpecies(_)
+            | AssetErrorKind::MissingFormationDirective(_)
+            | AssetErrorKind::FormationTooTall { .. } => None,
+        }
+    }
+}
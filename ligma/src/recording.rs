@@ -0,0 +1,289 @@
+use std::{fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    assets::Assets,
+    config::{Difficulty, GameConfig},
+    game::MS_PER_UPDATE,
+    ligma_result::{LigmaError, LigmaResult},
+    state::{Input, State},
+    viewport::Viewport,
+};
+
+/// A single game as it was played: where it started and what happened on every tick. Playing the
+/// events back on a fresh state reproduces the game exactly.
+#[derive(Debug, Clone)]
+pub struct Recording {
+    seed: u64,
+    difficulty: Difficulty,
+    viewport: Viewport,
+    /// `Assets::get_fingerprint` of the assets the game was played with.
+    assets: u64,
+    /// The config the game was played with, difficulty included.
+    config: GameConfig,
+    events: Vec<RecordedEvent>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordedEvent {
+    Step(Input),
+    /// The terminal was resized between two ticks.
+    Resize(Viewport),
+}
+
+/// On disk a recording is TOML. Runs of equal inputs are stored as `<ticks> <LRF or ->` and
+/// resizes as `resize <width> <height>`, the seed and the assets fingerprint are strings since
+/// TOML integers are signed.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RecordingFile {
+    version: u32,
+    seed: String,
+    difficulty: String,
+    assets: String,
+    width: u16,
+    height: u16,
+    events: Vec<String>,
+    config: GameConfig,
+}
+
+impl Recording {
+    /// Version 1 recordings were played with the alien layout in the config and version 2 ones
+    /// with a different random number generator, they can't be replayed.
+    const VERSION: u32 = 3;
+    /// Ten hours of play, so that a corrupted run length can't exhaust memory.
+    const MAX_EVENTS: u64 = 10 * 60 * 60 * 1000 / MS_PER_UPDATE as u64;
+
+    pub fn new(
+        seed: u64,
+        difficulty: Difficulty,
+        viewport: Viewport,
+        assets: &Assets,
+        config: GameConfig,
+    ) -> Recording {
+        Recording {
+            seed,
+            difficulty,
+            viewport,
+            assets: assets.get_fingerprint(),
+            config,
+            events: vec![],
+        }
+    }
+
+    pub fn load(file: &Path) -> LigmaResult<Recording> {
+        let error = |message: String| LigmaError::RecordingParsing {
+            file: file.display().to_string(),
+            message,
+        };
+
+        let content = fs::read_to_string(file).map_err(|source| LigmaError::RecordingLoading {
+            file: file.display().to_string(),
+            source,
+        })?;
+        let table = content
+            .parse::<toml::Table>()
+            .map_err(|err| error(err.to_string()))?;
+        let version = table.get("version").and_then(toml::Value::as_integer);
+
+        if version != Some(Self::VERSION as i64) {
+            return Err(error(format!(
+                "unsupported version {}, expected {}",
+                version.map_or("none".to_string(), |version| version.to_string()),
+                Self::VERSION
+            )));
+        }
+
+        let recording: RecordingFile = table
+            .try_into()
+            .map_err(|err: toml::de::Error| error(err.to_string()))?;
+
+        recording.config.validate()?;
+
+        let mut events = vec![];
+
+        for event in &recording.events {
+            let (parsed, count) =
+                parse_event(event).ok_or_else(|| error(format!("invalid event '{event}'")))?;
+
+            if count > Self::MAX_EVENTS - events.len() as u64 {
+                return Err(error(format!(
+                    "more than {} events, the recording is corrupted",
+                    Self::MAX_EVENTS
+                )));
+            }
+
+            events.extend((0..count).map(|_| parsed));
+        }
+
+        Ok(Recording {
+            seed: recording
+                .seed
+                .parse()
+                .map_err(|_| error(format!("invalid seed '{}'", recording.seed)))?,
+            difficulty: Difficulty::from_name(&recording.difficulty)
+                .ok_or_else(|| error(format!("unknown difficulty '{}'", recording.difficulty)))?,
+            viewport: Viewport::new(recording.width, recording.height),
+            assets: u64::from_str_radix(&recording.assets, 16)
+                .map_err(|_| error(format!("invalid assets fingerprint '{}'", recording.assets)))?,
+            config: recording.config,
+            events,
+        })
+    }
+
+    pub fn save(&self, file: &Path) -> LigmaResult<()> {
+        let recording = RecordingFile {
+            version: Self::VERSION,
+            seed: self.seed.to_string(),
+            difficulty: self.difficulty.get_name().to_string(),
+            assets: format!("{:016x}", self.assets),
+            width: self.viewport.get_width(),
+            height: self.viewport.get_height(),
+            events: format_events(&self.events),
+            config: self.config.clone(),
+        };
+
+        let error = |source| LigmaError::RecordingSaving {
+            file: file.display().to_string(),
+            source,
+        };
+
+        let content = toml::to_string(&recording).map_err(|err| error(io::Error::other(err)))?;
+
+        fs::write(file, content).map_err(error)
+    }
+
+    pub fn record_step(&mut self, input: &Input) {
+        self.events.push(RecordedEvent::Step(*input));
+    }
+
+    pub fn record_resize(&mut self, viewport: Viewport) {
+        self.events.push(RecordedEvent::Resize(viewport));
+    }
+
+    pub fn get_seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn get_difficulty(&self) -> Difficulty {
+        self.difficulty
+    }
+
+    pub fn get_viewport(&self) -> Viewport {
+        self.viewport
+    }
+
+    pub fn get_events(&self) -> &[RecordedEvent] {
+        &self.events
+    }
+
+    /// The state the recorded game started from. Sprites and the formation decide how a game
+    /// plays out, so other assets than the recorded ones are refused.
+    pub fn new_state(&self, assets: &Assets) -> LigmaResult<State> {
+        if assets.get_fingerprint() != self.assets {
+            return Err(LigmaError::RecordingAssetsMismatch);
+        }
+
+        Ok(State::with_config(
+            assets,
+            self.viewport,
+            self.seed,
+            self.config.clone(),
+        ))
+    }
+
+    /// Plays the whole recording back without rendering and returns the final state.
+    pub fn play(&self, assets: &Assets) -> LigmaResult<State> {
+        let mut state = self.new_state(assets)?;
+
+        for event in &self.events {
+            event.apply(&mut state);
+        }
+
+        Ok(state)
+    }
+}
+
+impl RecordedEvent {
+    pub fn apply(&self, state: &mut State) {
+        match self {
+            RecordedEvent::Step(input) => state.step(input),
+            RecordedEvent::Resize(viewport) => state.resize(*viewport),
+        }
+    }
+}
+
+fn format_events(events: &[RecordedEvent]) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+    let mut run: Option<(Input, u64)> = None;
+
+    let format_run = |(input, count): (Input, u64)| {
+        let mut flags = String::new();
+
+        for (set, flag) in [(input.left, 'L'), (input.right, 'R'), (input.fire, 'F')] {
+            if set {
+                flags.push(flag);
+            }
+        }
+
+        if flags.is_empty() {
+            flags.push('-');
+        }
+
+        format!("{count} {flags}")
+    };
+
+    for event in events {
+        match (event, run.as_mut()) {
+            (RecordedEvent::Step(input), Some((last, count))) if last == input => *count += 1,
+            (RecordedEvent::Step(input), _) => {
+                lines.extend(run.replace((*input, 1)).map(format_run));
+            }
+            (RecordedEvent::Resize(viewport), _) => {
+                lines.extend(run.take().map(format_run));
+                lines.push(format!(
+                    "resize {} {}",
+                    viewport.get_width(),
+                    viewport.get_height()
+                ));
+            }
+        }
+    }
+
+    lines.extend(run.map(format_run));
+    lines
+}
+
+/// Parses a line into an event and the number of times it repeats.
+fn parse_event(line: &str) -> Option<(RecordedEvent, u64)> {
+    let mut parts = line.split_whitespace();
+    let first = parts.next()?;
+
+    if first == "resize" {
+        let width = parts.next()?.parse().ok()?;
+        let height = parts.next()?.parse().ok()?;
+        let event = RecordedEvent::Resize(Viewport::new(width, height));
+        return parts.next().is_none().then_some((event, 1));
+    }
+
+    let count = first.parse::<u64>().ok()?;
+    let flags = parts.next()?;
+    let mut input = Input::default();
+
+    if flags != "-" {
+        for flag in flags.chars() {
+            match flag {
+                'L' => input.left = true,
+                'R' => input.right = true,
+                'F' => input.fire = true,
+                _ => return None,
+            }
+        }
+    }
+
+    parts
+        .next()
+        .is_none()
+        .then_some((RecordedEvent::Step(input), count))
+}
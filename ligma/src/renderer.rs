@@ -0,0 +1,461 @@
+use std::{
+    fmt::Debug,
+    io::{stdout, Result, Stdout, Write},
+};
+
+use crossterm::{
+    cursor, queue,
+    style::{self, Attribute, Color},
+    terminal,
+};
+
+use crate::{
+    frame_buffer::{Cell, FrameBuffer},
+    state::{Coord, RgbColor, State, Style},
+    viewport::Viewport,
+};
+
+const LIFE_ICON: &str = "▄█▄";
+/// Columns kept free between the parts of the HUD.
+const HUD_GAP: u16 = 2;
+
+/// Drawing backend. A frame is started with `clear`, drawn into and then shown with `present`.
+pub trait Renderer: Debug {
+    fn clear(&mut self) -> Result<()>;
+    fn draw_sprite(&mut self, sprite: &[Coord], color: Option<RgbColor>) -> Result<()>;
+    fn draw_text(&mut self, x: u16, y: u16, text: &str, color: Option<RgbColor>) -> Result<()>;
+    fn present(&mut self) -> Result<()>;
+    fn resize(&mut self, width: u16, height: u16);
+}
+
+/// Draws into a back buffer and on `present` writes only the cells that changed since the last
+/// frame, wrapped in a synchronized update so the terminal shows the frame at once.
+#[derive(Debug)]
+pub struct TerminalRenderer<W: Write = Stdout> {
+    std_out: W,
+    front: FrameBuffer,
+    back: FrameBuffer,
+    output: Vec<u8>,
+    full_redraw: bool,
+    last_frame_size: usize,
+}
+
+/// Renders into an in-memory frame buffer, e.g. for snapshot tests.
+#[derive(Debug)]
+pub struct GridRenderer {
+    back: FrameBuffer,
+    frame: FrameBuffer,
+}
+
+impl TerminalRenderer {
+    pub fn new(width: u16, height: u16) -> TerminalRenderer {
+        Self::with_writer(stdout(), width, height)
+    }
+}
+
+impl<W: Write> TerminalRenderer<W> {
+    /// Writes frames to `writer` instead of the terminal.
+    pub fn with_writer(writer: W, width: u16, height: u16) -> TerminalRenderer<W> {
+        TerminalRenderer {
+            std_out: writer,
+            front: FrameBuffer::new(width, height),
+            back: FrameBuffer::new(width, height),
+            output: vec![],
+            full_redraw: true,
+            last_frame_size: 0,
+        }
+    }
+
+    /// Number of bytes written to the terminal by the last `present`.
+    pub fn get_last_frame_size(&self) -> usize {
+        self.last_frame_size
+    }
+}
+
+impl<W: Write + Debug> Renderer for TerminalRenderer<W> {
+    fn clear(&mut self) -> Result<()> {
+        self.back.clear();
+        Ok(())
+    }
+
+    fn draw_sprite(&mut self, sprite: &[Coord], color: Option<RgbColor>) -> Result<()> {
+        self.back.draw_sprite(sprite, color);
+        Ok(())
+    }
+
+    fn draw_text(&mut self, x: u16, y: u16, text: &str, color: Option<RgbColor>) -> Result<()> {
+        self.back.draw_text(x, y, text, color);
+        Ok(())
+    }
+
+    fn present(&mut self) -> Result<()> {
+        self.output.clear();
+        queue!(self.output, terminal::BeginSynchronizedUpdate)?;
+
+        if self.full_redraw {
+            queue!(
+                self.output,
+                style::ResetColor,
+                terminal::Clear(terminal::ClearType::All)
+            )?;
+            self.front.clear();
+            self.full_redraw = false;
+        }
+
+        let mut cursor_position = None;
+        let mut current_style = None;
+
+        for (x, y, cell) in self.back.diff(&self.front) {
+            if cursor_position != Some((x, y)) {
+                queue!(self.output, cursor::MoveTo(x, y))?;
+            }
+
+            if current_style != Some(cell.style) {
+                queue_style(&mut self.output, current_style, cell.style)?;
+                current_style = Some(cell.style);
+            }
+
+            queue!(self.output, style::Print(cell.ch))?;
+            cursor_position = Some((x + 1, y));
+        }
+
+        if current_style.is_some_and(|style| style != Style::PLAIN) {
+            queue!(self.output, style::SetAttribute(Attribute::Reset))?;
+        }
+
+        queue!(self.output, terminal::EndSynchronizedUpdate)?;
+
+        self.std_out.write_all(&self.output)?;
+        self.std_out.flush()?;
+
+        self.last_frame_size = self.output.len();
+        std::mem::swap(&mut self.front, &mut self.back);
+
+        Ok(())
+    }
+
+    fn resize(&mut self, width: u16, height: u16) {
+        self.front = FrameBuffer::new(width, height);
+        self.back = FrameBuffer::new(width, height);
+        self.full_redraw = true;
+    }
+}
+
+impl GridRenderer {
+    pub fn new(width: u16, height: u16) -> GridRenderer {
+        GridRenderer {
+            back: FrameBuffer::new(width, height),
+            frame: FrameBuffer::new(width, height),
+        }
+    }
+
+    /// Last presented frame.
+    pub fn get_frame(&self) -> &FrameBuffer {
+        &self.frame
+    }
+
+    /// Cell of the last presented frame.
+    pub fn get_cell(&self, x: u16, y: u16) -> Option<&Cell> {
+        self.frame.get(x, y)
+    }
+
+    /// Characters of the last presented frame, one string per row.
+    pub fn get_lines(&self) -> Vec<String> {
+        self.frame.get_lines()
+    }
+}
+
+impl Renderer for GridRenderer {
+    fn clear(&mut self) -> Result<()> {
+        self.back.clear();
+        Ok(())
+    }
+
+    fn draw_sprite(&mut self, sprite: &[Coord], color: Option<RgbColor>) -> Result<()> {
+        self.back.draw_sprite(sprite, color);
+        Ok(())
+    }
+
+    fn draw_text(&mut self, x: u16, y: u16, text: &str, color: Option<RgbColor>) -> Result<()> {
+        self.back.draw_text(x, y, text, color);
+        Ok(())
+    }
+
+    fn present(&mut self) -> Result<()> {
+        self.frame.clone_from(&self.back);
+        Ok(())
+    }
+
+    fn resize(&mut self, width: u16, height: u16) {
+        self.back = FrameBuffer::new(width, height);
+        self.frame = FrameBuffer::new(width, height);
+    }
+}
+
+pub fn render_state(renderer: &mut dyn Renderer, state: &State) -> Result<()> {
+    draw_state(renderer, state)?;
+    renderer.present()
+}
+
+/// Renders the frozen game with a menu box on top of it, `selected` marking the chosen option.
+pub fn render_pause_menu(
+    renderer: &mut dyn Renderer,
+    state: &State,
+    options: &[&str],
+    selected: usize,
+) -> Result<()> {
+    let viewport = state.get_viewport();
+
+    let mut lines = vec!["PAUSED".to_string(), String::new()];
+    lines.extend(
+        options
+            .iter()
+            .enumerate()
+            .map(|(idx, option)| match idx == selected {
+                true => format!("> {option} <"),
+                false => format!("  {option}  "),
+            }),
+    );
+
+    let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0) as u16 + 4;
+    let height = lines.len() as u16 + 2;
+    let x = viewport.get_width().saturating_sub(width) / 2;
+    let y = viewport.get_height().saturating_sub(height) / 2;
+
+    draw_state(renderer, state)?;
+
+    let blank = " ".repeat(width as usize);
+    for row in 0..height {
+        renderer.draw_text(x, y + row, &blank, None)?;
+    }
+
+    for (idx, line) in lines.iter().enumerate() {
+        let line_x = x + (width - line.chars().count() as u16) / 2;
+        renderer.draw_text(line_x, y + 1 + idx as u16, line, None)?;
+    }
+
+    renderer.present()
+}
+
+/// Score and lives on the left, the wave centred and the seed on the right. Lives collapse to a
+/// count when their icons would run into the wave, and nothing is drawn over what's left of it.
+fn draw_hud(renderer: &mut dyn Renderer, state: &State) -> Result<()> {
+    let viewport = state.get_viewport();
+    let y = viewport.max_y - 1;
+    let width = |text: &str| text.chars().count() as u16;
+
+    let score = format!("SCORE: {}  LIVES: ", state.get_score());
+    let wave = format!("WAVE: {}", state.get_wave());
+    let seed = format!("SEED: {}", state.get_seed());
+
+    let score_x = 2;
+    let lives_x = score_x + width(&score);
+    let centred_wave_x = viewport.get_width().saturating_sub(width(&wave)) / 2;
+
+    let icons = vec![LIFE_ICON; state.get_lives()].join(" ");
+    let lives = match lives_x + width(&icons) + HUD_GAP <= centred_wave_x {
+        true => icons,
+        false => format!("{LIFE_ICON} x {}", state.get_lives()),
+    };
+
+    let wave_x = centred_wave_x.max(lives_x + width(&lives) + HUD_GAP);
+    let seed_x = viewport
+        .max_x
+        .saturating_sub(width(&seed))
+        .max(wave_x + width(&wave) + HUD_GAP);
+
+    renderer.draw_text(score_x, y, &score, None)?;
+    renderer.draw_text(lives_x, y, &lives, Some(*state.get_player_color()))?;
+    renderer.draw_text(wave_x, y, &wave, None)?;
+    renderer.draw_text(seed_x, y, &seed, None)
+}
+
+fn draw_state(renderer: &mut dyn Renderer, state: &State) -> Result<()> {
+    let player_color = Some(*state.get_player_color());
+
+    renderer.clear()?;
+
+    if !state.player.is_exploding() {
+        renderer.draw_sprite(&state.player.position, player_color)?;
+    }
+
+    draw_hud(renderer, state)?;
+
+    if let Some(laser) = &state.player.laser {
+        renderer.draw_sprite(&laser.position, player_color)?;
+    }
+
+    for aliens_row in &state.aliens.aliens_rows {
+        for alien in &aliens_row.aliens {
+            renderer.draw_sprite(&alien.position, None)?;
+        }
+    }
+
+    for laser in &state.aliens.lasers {
+        renderer.draw_sprite(&laser.position, None)?;
+    }
+
+    for bunker in &state.bunkers.positions {
+        renderer.draw_sprite(&bunker.position, player_color)?;
+    }
+
+    if let Some(ufo) = &state.ufo {
+        renderer.draw_sprite(&ufo.position, None)?;
+    }
+
+    for effect in state.effects.iter() {
+        renderer.draw_sprite(&effect.position, effect.color)?;
+    }
+
+    if let Some(bonus) = &state.bonus {
+        renderer.draw_text(bonus.x, bonus.y, &bonus.points.to_string(), None)?;
+    }
+
+    Ok(())
+}
+
+pub fn render_message(
+    renderer: &mut dyn Renderer,
+    viewport: &Viewport,
+    message: &str,
+) -> Result<()> {
+    render_lines(renderer, viewport, &[message.to_string()])
+}
+
+/// Renders the lines centered as a block, each line centered on its own.
+pub fn render_lines(
+    renderer: &mut dyn Renderer,
+    viewport: &Viewport,
+    lines: &[String],
+) -> Result<()> {
+    let y = (viewport.max_y / 2).saturating_sub(lines.len() as u16 / 2);
+
+    renderer.clear()?;
+
+    for (idx, line) in lines.iter().enumerate() {
+        let x = viewport
+            .get_width()
+            .saturating_sub(line.chars().count() as u16)
+            / 2;
+
+        renderer.draw_text(x, y + idx as u16, line, None)?;
+    }
+
+    renderer.present()
+}
+
+/// Switches the terminal from `previous` to `style`. Attributes and colors that are turned off
+/// need a full reset, anything else is set on top of the previous style.
+fn queue_style(output: &mut Vec<u8>, previous: Option<Style>, style: Style) -> Result<()> {
+    let reset = match previous {
+        Some(previous) => {
+            previous.attributes != style.attributes
+                || previous.foreground.is_some() && style.foreground.is_none()
+                || previous.background.is_some() && style.background.is_none()
+        }
+        None => true,
+    };
+
+    if reset {
+        queue!(output, style::SetAttribute(Attribute::Reset))?;
+
+        let attributes = style.attributes;
+
+        for (enabled, attribute) in [
+            (attributes.bold, Attribute::Bold),
+            (attributes.dim, Attribute::Dim),
+            (attributes.italic, Attribute::Italic),
+            (attributes.underlined, Attribute::Underlined),
+            (attributes.reversed, Attribute::Reverse),
+        ] {
+            if enabled {
+                queue!(output, style::SetAttribute(attribute))?;
+            }
+        }
+    }
+
+    let previous = previous.filter(|_| !reset).unwrap_or(Style::PLAIN);
+
+    if let Some(color) = style.foreground.filter(|&c| previous.foreground != Some(c)) {
+        queue!(output, style::SetForegroundColor(to_terminal_color(color)))?;
+    }
+
+    if let Some(color) = style.background.filter(|&c| previous.background != Some(c)) {
+        queue!(output, style::SetBackgroundColor(to_terminal_color(color)))?;
+    }
+
+    Ok(())
+}
+
+fn to_terminal_color(color: RgbColor) -> Color {
+    Color::Rgb {
+        r: color.r,
+        g: color.g,
+        b: color.b,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::*;
+    use crate::assets::Assets;
+
+    #[test]
+    fn unchanged_frame_writes_far_less_than_full_redraw() {
+        let assets = Assets::embedded().unwrap();
+        let viewport = Viewport::new(120, 50);
+        let state = State::with_seed(&assets, viewport, 1);
+        let mut renderer = TerminalRenderer::with_writer(io::sink(), 120, 50);
+
+        render_state(&mut renderer, &state).unwrap();
+        let full_redraw = renderer.get_last_frame_size();
+
+        render_state(&mut renderer, &state).unwrap();
+        let unchanged = renderer.get_last_frame_size();
+
+        assert!(
+            unchanged * 20 < full_redraw,
+            "unchanged frame wrote {unchanged} bytes, full redraw {full_redraw}"
+        );
+    }
+
+    #[test]
+    fn grid_snapshot_of_a_new_game() {
+        let assets = Assets::embedded().unwrap();
+        let state = State::with_seed(&assets, Viewport::new(90, 44), 7);
+        let mut renderer = GridRenderer::new(90, 44);
+
+        render_state(&mut renderer, &state).unwrap();
+        let lines = renderer.get_lines();
+
+        assert_eq!(lines.len(), 44);
+        assert!(lines.iter().all(|line| line.chars().count() == 90));
+        assert_eq!(
+            lines[5..9].iter().map(|l| l.trim_end()).collect::<Vec<_>>(),
+            [
+                "     ▄██▄          ▄██▄          ▄██▄",
+                "   ▄█▀██▀█▄      ▄█▀██▀█▄      ▄█▀██▀█▄",
+                "   ▀█▀██▀█▀      ▀█▀██▀█▀      ▀█▀██▀█▀",
+                "   ▀▄    ▄▀      ▀▄    ▄▀      ▀▄    ▄▀",
+            ]
+        );
+        assert_eq!(
+            lines[38..42]
+                .iter()
+                .map(|l| l.trim_end())
+                .collect::<Vec<_>>(),
+            [
+                "       ▄█▄",
+                "  ▄▄▄▄▄███▄▄▄▄▄",
+                " ███████████████",
+                " ███████████████",
+            ]
+        );
+        assert_eq!(
+            lines[42].trim_end(),
+            "  SCORE: 0  LIVES: ▄█▄ ▄█▄ ▄█▄           WAVE: 1                                  SEED: 7"
+        );
+    }
+}
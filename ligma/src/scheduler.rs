@@ -0,0 +1,70 @@
+pub type Tick = u64;
+
+/// Counts simulation ticks. Everything in the simulation is scheduled relative to it, so a game
+/// plays out the same way no matter how fast (or unevenly) ticks are fed to it.
+#[derive(Debug, Default)]
+pub struct Scheduler {
+    tick: Tick,
+}
+
+/// Fires at most once every `period` ticks. `last` may lie in the future, which delays the first
+/// firing.
+#[derive(Debug, Clone)]
+pub struct Timer {
+    period: u64,
+    last: Tick,
+}
+
+impl Scheduler {
+    pub fn new() -> Scheduler {
+        Scheduler::default()
+    }
+
+    pub fn now(&self) -> Tick {
+        self.tick
+    }
+
+    pub fn advance(&mut self) {
+        self.tick += 1;
+    }
+
+    pub fn timer(&self, period: u64) -> Timer {
+        self.delayed_timer(period, 0)
+    }
+
+    pub fn delayed_timer(&self, period: u64, delay: u64) -> Timer {
+        Timer::new(period, self.tick + delay)
+    }
+}
+
+impl Timer {
+    pub fn new(period: u64, last: Tick) -> Timer {
+        Timer { period, last }
+    }
+
+    pub fn is_ready(&self, now: Tick) -> bool {
+        now >= self.last + self.period
+    }
+
+    pub fn restart(&mut self, now: Tick) {
+        self.last = now;
+    }
+
+    pub fn restart_delayed(&mut self, now: Tick, delay: u64) {
+        self.last = now + delay;
+    }
+
+    /// Returns whether the timer is due and, if so, restarts it.
+    pub fn fire(&mut self, now: Tick) -> bool {
+        if !self.is_ready(now) {
+            return false;
+        }
+
+        self.restart(now);
+        true
+    }
+
+    pub fn set_period(&mut self, period: u64) {
+        self.period = period;
+    }
+}
@@ -0,0 +1,82 @@
+use std::sync::Arc;
+
+use crate::state::Coord;
+
+#[derive(Debug, Clone)]
+pub struct Sprite {
+    pub frames: Vec<Frame>,
+}
+
+/// Cells of one frame, shown for `duration` animation steps.
+#[derive(Debug, Clone)]
+pub struct Frame {
+    pub cells: Vec<Coord>,
+    pub duration: u16,
+}
+
+/// Playback position within a sprite's frames.
+#[derive(Debug, Clone)]
+pub struct Animation {
+    sprite: Arc<Sprite>,
+    frame: usize,
+    steps_left: u16,
+}
+
+impl Sprite {
+    pub fn get_first_frame(&self) -> &Frame {
+        &self.frames[0]
+    }
+
+    /// Columns taken by the widest frame.
+    pub fn get_width(&self) -> u16 {
+        self.frames
+            .iter()
+            .flat_map(|f| &f.cells)
+            .map(|c| c.x + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Number of animation steps a full cycle through the frames takes.
+    pub fn get_duration(&self) -> u64 {
+        self.frames.iter().map(|f| f.duration as u64).sum()
+    }
+}
+
+impl Animation {
+    pub fn new(sprite: Arc<Sprite>) -> Animation {
+        let steps_left = sprite.get_first_frame().duration;
+
+        Animation {
+            sprite,
+            frame: 0,
+            steps_left,
+        }
+    }
+
+    pub fn get_sprite(&self) -> &Sprite {
+        &self.sprite
+    }
+
+    pub fn get_frame(&self) -> &Frame {
+        &self.sprite.frames[self.frame]
+    }
+
+    /// Advances the animation by one step and returns whether the frame changed.
+    pub fn advance(&mut self) -> bool {
+        if self.sprite.frames.len() < 2 {
+            return false;
+        }
+
+        self.steps_left = self.steps_left.saturating_sub(1);
+
+        if self.steps_left > 0 {
+            return false;
+        }
+
+        self.frame = (self.frame + 1) % self.sprite.frames.len();
+        self.steps_left = self.get_frame().duration;
+
+        true
+    }
+}
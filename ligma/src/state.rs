@@ -1,22 +1,51 @@
+use std::sync::Arc;
+
 use crate::{
-    game::{MS_PER_UPDATE, VIEWPORT_MAX_X, VIEWPORT_MAX_Y, VIEWPORT_MIN_X, VIEWPORT_MIN_Y},
-    ligma_result::LigmaResult,
+    assets::Assets,
+    config::{AliensConfig, GameConfig, PlayerConfig, UfoConfig},
+    effects::Effects,
+    formation::Formation,
+    scheduler::{Scheduler, Tick, Timer},
+    sprite::{Animation, Sprite},
+    viewport::Viewport,
 };
-use rand::Rng;
-use std::time::{Duration, SystemTime};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 
 #[derive(Debug)]
 pub struct State {
     pub player: Player,
     pub aliens: Aliens,
     pub bunkers: Bunkers,
-    prototypes: Prototypes,
+    pub ufo: Option<Ufo>,
+    pub bonus: Option<Bonus>,
+    pub effects: Effects,
+    assets: Assets,
     player_color: RgbColor,
+    scheduler: Scheduler,
+    /// A named algorithm, unlike `StdRng`, so that a seed plays out the same game across rand
+    /// releases.
+    rng: ChaCha8Rng,
+    seed: u64,
+    viewport: Viewport,
+    wave: u32,
+    score: u32,
+    ufo_timer: Timer,
+    config: GameConfig,
+    extra_life_awarded: bool,
 }
 
-#[derive(Debug)]
-struct Prototypes {
-    laser: Vec<Coord>,
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Input {
+    pub left: bool,
+    pub right: bool,
+    pub fire: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Playing,
+    Lost,
 }
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
@@ -24,34 +53,72 @@ pub struct Coord {
     pub x: u16,
     pub y: u16,
     pub ch: char,
+    pub style: Style,
 }
 
 #[derive(Debug)]
 pub struct Player {
-    pub health: usize,
+    pub lives: usize,
     pub position: Vec<Coord>,
     pub laser: Option<Laser>,
+    /// Set while the ship explodes after a hit.
+    respawn_at: Option<Tick>,
+    /// Paces movement while a direction is held.
+    move_timer: Timer,
+    config: PlayerConfig,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct RgbColor {
     pub r: u8,
     pub g: u8,
     pub b: u8,
 }
 
+/// Look of a single cell. Unset colors fall back to the color the sprite is drawn with, or to the
+/// terminal default.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Style {
+    pub foreground: Option<RgbColor>,
+    pub background: Option<RgbColor>,
+    pub attributes: Attributes,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Attributes {
+    pub bold: bool,
+    pub dim: bool,
+    pub italic: bool,
+    pub underlined: bool,
+    pub reversed: bool,
+}
+
 trait Row {
-    fn generate(position: Vec<Coord>) -> Self;
+    type Prototype: ?Sized;
+
+    fn generate(prototype: &Self::Prototype, x: u16, y: u16) -> Self;
 }
 
 #[derive(Debug)]
 pub struct Alien {
     pub position: Vec<Coord>,
+    x: u16,
+    y: u16,
+    animation: Animation,
 }
 
 impl Row for Alien {
-    fn generate(position: Vec<Coord>) -> Alien {
-        Alien { position }
+    type Prototype = Arc<Sprite>;
+
+    fn generate(sprite: &Arc<Sprite>, x: u16, y: u16) -> Alien {
+        let animation = Animation::new(Arc::clone(sprite));
+
+        Alien {
+            position: shift_prototype(&animation.get_frame().cells, x, y),
+            x,
+            y,
+            animation,
+        }
     }
 }
 
@@ -61,36 +128,68 @@ pub struct Bunker {
 }
 
 impl Row for Bunker {
-    fn generate(position: Vec<Coord>) -> Bunker {
-        Bunker { position }
+    type Prototype = [Coord];
+
+    fn generate(prototype: &[Coord], x: u16, y: u16) -> Bunker {
+        Bunker {
+            position: shift_prototype(prototype, x, y),
+        }
     }
 }
 
 #[derive(Debug)]
 pub struct Bunkers {
     pub positions: Vec<Bunker>,
+    width: u16,
+    number: u16,
 }
 
 #[derive(Debug)]
 pub struct AliensRow {
     pub aliens: Vec<Alien>,
-    last_update: SystemTime,
+    points: u32,
+    timer: Timer,
 }
 
 #[derive(Debug)]
 pub struct Aliens {
     pub aliens_rows: Vec<AliensRow>,
     pub lasers: Vec<Laser>,
-    times_slower_than_cycle: u128,
+    /// Aliens the wave started with.
+    initial_count: usize,
+    base_slower_than_cycle: u64,
+    times_slower_than_cycle: u64,
     direction: AlienDirection,
-    last_shot: SystemTime,
+    shot_timer: Timer,
+    /// Ticks between two neighbouring rows starting to march.
+    stagger: u64,
+    /// Columns between two neighbouring aliens of a row, narrowed when the viewport shrinks.
+    spacing: u16,
+    row_spacing: u16,
+    config: AliensConfig,
 }
 
 #[derive(Debug, Clone)]
 pub struct Laser {
     pub position: Vec<Coord>,
-    last_update: SystemTime,
-    times_slower_than_cycle: u128,
+    timer: Timer,
+}
+
+/// Bonus saucer crossing the top of the screen.
+#[derive(Debug)]
+pub struct Ufo {
+    pub position: Vec<Coord>,
+    direction: AlienDirection,
+    timer: Timer,
+}
+
+/// Points awarded for a UFO, shown where it was hit for a short while.
+#[derive(Debug)]
+pub struct Bonus {
+    pub x: u16,
+    pub y: u16,
+    pub points: u32,
+    expires_at: Tick,
 }
 
 #[derive(Debug, Clone)]
@@ -99,90 +198,320 @@ pub enum AlienDirection {
     Right,
 }
 
-const U16_CONVERT_ERROR: &str = "error converting coords to u16 while parsing assets";
-const ASSETS_CHAR_ERROR: &str = "error getting char while parsing assets";
-const ASSETS_PARSING_ERROR: &str = "error parsing assets content";
-
 impl State {
-    pub fn new() -> LigmaResult<State> {
-        let player_model = include_str!("./assets/player.txt");
-        let laser_model = include_str!("./assets/laser.txt");
+    pub fn new(assets: &Assets, viewport: Viewport) -> State {
+        Self::with_seed(assets, viewport, rand::random())
+    }
 
-        let player_prototype = parse_prototype(player_model)?;
-        let laser_prototype = parse_prototype(laser_model)?;
+    /// The same seed fed with the same inputs always plays out the same game.
+    pub fn with_seed(assets: &Assets, viewport: Viewport, seed: u64) -> State {
+        Self::with_config(assets, viewport, seed, GameConfig::default())
+    }
 
-        Ok(State {
+    /// Expects a config that passed `GameConfig::validate`.
+    pub fn with_config(
+        assets: &Assets,
+        viewport: Viewport,
+        seed: u64,
+        config: GameConfig,
+    ) -> State {
+        let scheduler = Scheduler::new();
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let ufo_timer = scheduler.timer(Ufo::get_spawn_delay(&mut rng, &config.ufo));
+
+        State {
             player: Player {
-                health: Player::HEALTH,
-                position: shift_prototype(&player_prototype, Player::INITIAL_X, Player::INITIAL_Y),
+                lives: config.player.lives,
+                position: shift_prototype(
+                    &assets.player,
+                    Player::INITIAL_X,
+                    Player::get_initial_y(&viewport),
+                ),
                 laser: None,
+                respawn_at: None,
+                move_timer: Timer::new(config.player.move_slower_than_cycle, 0),
+                config: config.player.clone(),
             },
-            aliens: Aliens::init()?,
-            bunkers: Bunkers::init()?,
-            prototypes: Prototypes {
-                laser: laser_prototype,
-            },
+            aliens: Aliens::init(&scheduler, &viewport, assets, &config.aliens, 1),
+            bunkers: Bunkers::init(&viewport, assets, config.bunkers.number),
+            ufo: None,
+            bonus: None,
+            effects: Effects::default(),
+            assets: assets.clone(),
             player_color: RgbColor {
                 r: 32,
                 g: 255,
                 b: 32,
             },
-        })
+            scheduler,
+            rng,
+            seed,
+            viewport,
+            wave: 1,
+            score: 0,
+            ufo_timer,
+            config,
+            extra_life_awarded: false,
+        }
     }
 
-    pub fn player_go_left(&mut self) {
-        self.player.go_left();
+    /// Lays the player, the bunkers and the formation out for the new viewport. Aliens keep their
+    /// distance to the bunkers, keep marching and turn around at the new edges.
+    pub fn resize(&mut self, viewport: Viewport) {
+        let previous = std::mem::replace(&mut self.viewport, viewport);
+
+        self.player.relayout(&previous, &viewport);
+        self.bunkers.relayout(&previous, &viewport);
+        self.aliens.relayout(&previous, &viewport);
     }
 
-    pub fn player_go_right(&mut self) {
-        self.player.go_right();
+    /// Applies the input and advances the simulation by a single tick. Does nothing once the game
+    /// is over.
+    pub fn step(&mut self, input: &Input) {
+        if self.get_status() != Status::Playing {
+            return;
+        }
+
+        if self.player.is_exploding() {
+            self.update();
+            return;
+        }
+
+        if (input.left || input.right) && self.player.move_timer.fire(self.scheduler.now()) {
+            if input.left {
+                self.player_go_left();
+            }
+
+            if input.right {
+                self.player_go_right();
+            }
+        }
+
+        if input.fire {
+            self.player_shoot();
+        }
+
+        self.update();
     }
 
-    pub fn player_shoot(&mut self) {
+    /// Whether a move passed to the next `step` is used up: the ship either moves, or can't move
+    /// at all while exploding. Otherwise the ship is still waiting for its move timer.
+    pub fn takes_move(&self) -> bool {
+        self.player.is_exploding()
+            || self.get_status() != Status::Playing
+            || self.player.move_timer.is_ready(self.scheduler.now())
+    }
+
+    pub fn get_status(&self) -> Status {
+        if self.player.lives == 0 && !self.player.is_exploding() || self.aliens_invaded() {
+            return Status::Lost;
+        }
+
+        Status::Playing
+    }
+
+    pub fn get_wave(&self) -> u32 {
+        self.wave
+    }
+
+    pub fn get_score(&self) -> u32 {
+        self.score
+    }
+
+    pub fn get_lives(&self) -> usize {
+        self.player.lives
+    }
+
+    pub fn get_tick(&self) -> Tick {
+        self.scheduler.now()
+    }
+
+    pub fn get_seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn get_viewport(&self) -> &Viewport {
+        &self.viewport
+    }
+
+    fn player_go_left(&mut self) {
+        self.player.go_left(&self.viewport);
+    }
+
+    fn player_go_right(&mut self) {
+        self.player.go_right(&self.viewport);
+    }
+
+    fn player_shoot(&mut self) {
         if self.player.laser.is_some() {
             return;
         }
 
-        self.player.shoot(&self.prototypes.laser);
+        self.player.shoot(&self.assets.laser, &self.scheduler);
     }
 
-    pub fn update_player_laser(&mut self) {
-        let laser = self.player.laser.as_mut();
+    fn update(&mut self) {
+        self.scheduler.advance();
+
+        self.effects.update(self.scheduler.now());
+        self.update_player();
+        self.update_player_laser();
+        self.update_aliens();
+        self.update_aliens_lasers();
+        self.update_ufo();
+        self.apply_collisions();
+        self.award_extra_life();
+
+        if self.get_aliens_count() == 0 {
+            self.next_wave();
+        }
+    }
+
+    /// Spawns the next formation, lower and faster than the last one. The player keeps its
+    /// lives and position, the bunkers are rebuilt.
+    fn next_wave(&mut self) {
+        self.wave += 1;
+        self.player.laser = None;
+        self.aliens = Aliens::init(
+            &self.scheduler,
+            &self.viewport,
+            &self.assets,
+            &self.config.aliens,
+            self.wave,
+        );
+        self.bunkers = Bunkers::init(&self.viewport, &self.assets, self.config.bunkers.number);
+    }
 
-        if laser.is_none() {
+    /// Respawns the ship at the start position once its explosion is over.
+    fn update_player(&mut self) {
+        if self
+            .player
+            .respawn_at
+            .is_none_or(|respawn_at| self.scheduler.now() < respawn_at)
+        {
             return;
         }
 
-        let laser = laser.unwrap();
+        self.player.respawn_at = None;
 
-        if laser.last_update.elapsed().unwrap().as_millis()
-            < laser.times_slower_than_cycle * MS_PER_UPDATE
-        {
+        if self.player.lives > 0 {
+            self.player.position = shift_prototype(
+                &self.assets.player,
+                Player::INITIAL_X,
+                Player::get_initial_y(&self.viewport),
+            );
+        }
+    }
+
+    fn award_extra_life(&mut self) {
+        if self.extra_life_awarded {
+            return;
+        }
+
+        let score = self.config.extra_life_score;
+
+        if score > 0 && self.score >= score {
+            self.player.lives += 1;
+            self.extra_life_awarded = true;
+        }
+    }
+
+    fn update_player_laser(&mut self) {
+        let Some(laser) = self.player.laser.as_mut() else {
+            return;
+        };
+
+        if !laser.timer.fire(self.scheduler.now()) {
             return;
         }
 
-        laser.last_update = SystemTime::now();
+        let speed = self.config.player.laser_speed;
 
         if laser
             .position
             .iter()
-            .find(|p| p.y as i16 - Player::LASER_SPEED < VIEWPORT_MIN_Y as i16)
-            .is_some()
+            .any(|p| p.y < self.viewport.min_y + speed)
         {
+            let x = laser.position[0].x;
+
             self.player.laser = None;
+            self.effects.spawn_centered(
+                &self.assets.spark,
+                x,
+                self.viewport.min_y,
+                None,
+                &self.scheduler,
+            );
             return;
         }
 
         laser.position.iter_mut().for_each(|p| {
-            p.y = (p.y as i16 - Player::LASER_SPEED) as u16;
+            p.y -= speed;
         });
     }
 
-    pub fn update_aliens(&mut self) {
-        self.aliens.update();
+    fn update_aliens(&mut self) {
+        self.aliens.update(self.scheduler.now(), &self.viewport);
+    }
+
+    /// Flies the UFO, or spawns one once the spawn timer is up.
+    fn update_ufo(&mut self) {
+        let now = self.scheduler.now();
+
+        if self.bonus.as_ref().is_some_and(|b| now >= b.expires_at) {
+            self.bonus = None;
+        }
+
+        match self.ufo.as_mut() {
+            Some(ufo) => {
+                if ufo.timer.fire(now) && !ufo.fly(&self.viewport) {
+                    self.ufo = None;
+                    self.restart_ufo_timer();
+                }
+            }
+            None => {
+                if self.ufo_timer.is_ready(now) {
+                    self.ufo = Some(Ufo::spawn(
+                        &self.assets.ufo,
+                        &self.viewport,
+                        &mut self.rng,
+                        self.config.ufo.slower_than_cycle,
+                        now,
+                    ));
+                }
+            }
+        }
     }
 
-    pub fn apply_collisions(&mut self) {
+    fn restart_ufo_timer(&mut self) {
+        self.ufo_timer
+            .set_period(Ufo::get_spawn_delay(&mut self.rng, &self.config.ufo));
+        self.ufo_timer.restart(self.scheduler.now());
+    }
+
+    fn hit_ufo(&mut self) {
+        let Some(ufo) = self.ufo.take() else {
+            return;
+        };
+
+        let bonuses = &self.config.ufo.bonuses;
+        let points = bonuses[self.rng.gen_range(0..bonuses.len())];
+        let text_width = points.to_string().len() as u16;
+        let x = ufo.position.iter().map(|p| p.x).min().unwrap_or(0);
+        let y = ufo.position.iter().map(|p| p.y).min().unwrap_or(0);
+
+        self.score = self.score.saturating_add(points);
+        self.bonus = Some(Bonus {
+            x: x + (get_width(&self.assets.ufo).saturating_sub(text_width)) / 2,
+            y: y + 1,
+            points,
+            expires_at: self.scheduler.now() + Ufo::BONUS_DISPLAY_TICKS,
+        });
+
+        self.restart_ufo_timer();
+    }
+
+    fn apply_collisions(&mut self) {
         self.handle_player_laser();
         self.handle_aliens_lasers();
     }
@@ -191,10 +520,22 @@ impl State {
         &self.player_color
     }
 
-    pub fn update_aliens_lasers(&mut self) {
-        self.aliens.update_existing_aliens_lasers();
+    fn update_aliens_lasers(&mut self) {
+        let now = self.scheduler.now();
+
         self.aliens
-            .shoot(&self.player.position, &self.prototypes.laser);
+            .update_existing_aliens_lasers(now, &self.viewport);
+
+        if self.player.is_exploding() {
+            return;
+        }
+
+        self.aliens.shoot(
+            &self.player.position,
+            &self.assets.laser,
+            &mut self.rng,
+            now,
+        );
     }
 
     pub fn get_aliens_count(&self) -> usize {
@@ -209,15 +550,29 @@ impl State {
         let laser = self.player.laser.clone().unwrap().position;
 
         for bunker in self.bunkers.positions.iter_mut() {
-            match bunker.remove_shot_positions(&laser) {
-                true => {
-                    self.player.laser = None;
-                    return;
-                }
-                false => continue,
+            if let Some(shot_position) = bunker.remove_shot_positions(&laser) {
+                self.player.laser = None;
+                self.effects.spawn_centered(
+                    &self.assets.spark,
+                    shot_position.x,
+                    shot_position.y,
+                    None,
+                    &self.scheduler,
+                );
+                return;
             }
         }
 
+        if self
+            .ufo
+            .as_ref()
+            .is_some_and(|ufo| ufo.position.iter().any(|p| collides_with_laser(&laser, p)))
+        {
+            self.player.laser = None;
+            self.hit_ufo();
+            return;
+        }
+
         for aliens_row in self.aliens.aliens_rows.iter_mut().rev() {
             for (idx, alien) in aliens_row.aliens.iter().enumerate() {
                 let killed_alien = alien
@@ -225,8 +580,16 @@ impl State {
                     .iter()
                     .find(|&p| collides_with_laser(&laser, p));
 
-                if let Some(_) = killed_alien {
-                    aliens_row.aliens.remove(idx);
+                if killed_alien.is_some() {
+                    let alien = aliens_row.aliens.remove(idx);
+                    self.effects.spawn(
+                        &self.assets.alien_explosion,
+                        alien.x,
+                        alien.y,
+                        None,
+                        &self.scheduler,
+                    );
+                    self.score = self.score.saturating_add(aliens_row.points);
                     self.player.laser = None;
                     self.aliens.set_speed();
 
@@ -237,40 +600,69 @@ impl State {
     }
 
     fn handle_aliens_lasers(&mut self) {
-        if self.bunkers.positions.len() == 0 {
+        if self.bunkers.positions.is_empty() {
             return;
         }
 
+        let mut player_shot = false;
+
         self.aliens.lasers.retain(|laser| {
             for bunker in self.bunkers.positions.iter_mut() {
-                match bunker.remove_shot_positions(&laser.position) {
-                    true => return false,
-                    false => (),
+                if let Some(shot_position) = bunker.remove_shot_positions(&laser.position) {
+                    self.effects.spawn_centered(
+                        &self.assets.spark,
+                        shot_position.x,
+                        shot_position.y,
+                        None,
+                        &self.scheduler,
+                    );
+                    return false;
                 }
             }
 
-            if self.player.is_shot(&laser.position) {
-                return false;
-            }
+            let shot = !player_shot && self.player.is_shot(&laser.position);
+            player_shot |= shot;
 
-            return true;
+            !shot
         });
+
+        if player_shot {
+            self.explode_player();
+        }
+    }
+
+    /// Blows the ship up and holds alien fire until it respawns.
+    fn explode_player(&mut self) {
+        let x = self.player.position.iter().map(|p| p.x).min().unwrap_or(0);
+        let y = self.player.position.iter().map(|p| p.y).min().unwrap_or(0);
+
+        let respawn_at = self.effects.spawn(
+            &self.assets.player_explosion,
+            x,
+            y,
+            Some(self.player_color),
+            &self.scheduler,
+        );
+
+        self.aliens.lasers.clear();
+        self.player.laser = None;
+        self.player.respawn_at = Some(respawn_at);
     }
 
     pub fn aliens_invaded(&self) -> bool {
-        self.aliens.invaded()
+        self.aliens.invaded(Bunkers::get_initial_y(&self.viewport))
     }
 }
 
 impl Player {
-    const SPEED: i16 = 2;
-    const LASER_SPEED: i16 = 1;
-    const HEALTH: usize = 3;
     const INITIAL_X: u16 = 1;
-    const INITIAL_Y: u16 = VIEWPORT_MAX_Y - 5;
-    const LASER_SLOWER_THAN_CYCLE: u128 = 1;
+    const BOTTOM_OFFSET: u16 = 5;
 
-    fn shoot(&mut self, prototype: &Vec<Coord>) {
+    fn get_initial_y(viewport: &Viewport) -> u16 {
+        viewport.max_y.saturating_sub(Self::BOTTOM_OFFSET)
+    }
+
+    fn shoot(&mut self, prototype: &[Coord], scheduler: &Scheduler) {
         let tip_position = self.position.first().unwrap();
         let position = shift_prototype(
             prototype,
@@ -280,197 +672,364 @@ impl Player {
 
         self.laser = Some(Laser {
             position,
-            last_update: SystemTime::now(),
-            times_slower_than_cycle: Self::LASER_SLOWER_THAN_CYCLE,
+            timer: scheduler.timer(self.config.laser_slower_than_cycle),
         })
     }
 
-    fn go_left(&mut self) {
-        if self
-            .position
-            .iter()
-            .find(|p| p.x <= VIEWPORT_MIN_X)
-            .is_some()
-        {
-            return;
-        }
+    fn go_left(&mut self, viewport: &Viewport) {
+        self.shift_by(-(self.config.speed as i32), viewport);
+    }
 
-        self.shift_by(-Self::SPEED);
+    fn go_right(&mut self, viewport: &Viewport) {
+        self.shift_by(self.config.speed as i32, viewport);
     }
 
-    fn go_right(&mut self) {
-        if self
-            .position
-            .iter()
-            .find(|p| p.x >= VIEWPORT_MAX_X)
-            .is_some()
-        {
+    /// Moves the ship sideways, stopping it at the edges of the viewport.
+    fn shift_by(&mut self, x_shift: i32, viewport: &Viewport) {
+        let (Some(left), Some(right)) = (
+            self.position.iter().map(|p| p.x).min(),
+            self.position.iter().map(|p| p.x).max(),
+        ) else {
             return;
-        }
+        };
 
-        self.shift_by(Self::SPEED);
+        let rightmost = (viewport.max_x + 1)
+            .saturating_sub(right - left + 1)
+            .max(viewport.min_x);
+        let x = (left as i32 + x_shift).clamp(viewport.min_x as i32, rightmost as i32);
+
+        shift_coords(&mut self.position, x - left as i32, 0);
     }
 
-    fn shift_by(&mut self, x_shift: i16) {
-        self.position.iter_mut().for_each(|c| {
-            c.x = (c.x as i16 + x_shift) as u16;
-        });
+    fn relayout(&mut self, previous: &Viewport, viewport: &Viewport) {
+        let y_shift = Self::get_initial_y(viewport) as i32 - Self::get_initial_y(previous) as i32;
+
+        shift_coords(&mut self.position, 0, y_shift);
+        self.shift_by(0, viewport);
     }
 
-    fn is_shot(&mut self, laser: &Vec<Coord>) -> bool {
+    fn is_shot(&mut self, laser: &[Coord]) -> bool {
+        if self.is_exploding() {
+            return false;
+        }
+
         match self.position.iter().find(|p| collides_with_laser(laser, p)) {
             Some(_) => {
-                self.health -= 1;
+                self.lives -= 1;
                 true
             }
             None => false,
         }
     }
+
+    pub fn is_exploding(&self) -> bool {
+        self.respawn_at.is_some()
+    }
 }
 
 impl Aliens {
-    const INITIAL_X: u16 = 1;
-    const INITIAL_Y: u16 = 1;
-    const NUMBER: u16 = 11;
-    const SLOWER_THAN_CYCLE: u128 = 100;
     const X_SHIFT_PER_UPDATE: i16 = 1;
     const Y_SHIFT_PER_UPDATE: i16 = 2;
-    const ROWS_DELAY_SHIFT: u64 = 20;
-    const ROWS_NUMBER: usize = 5;
-    const STEP: u16 = 14;
-    const MAX_LASERS_AT_A_TIME: usize = 3;
-    const LASER_SPEED: u16 = 2;
-    const SHOTS_MIN_INTERVAL: u128 = 200;
-    const LASER_SLOWER_THAN_CYCLE: u128 = 25;
-
-    fn init() -> LigmaResult<Aliens> {
-        let step: usize = 5;
-
-        let squid_model = include_str!("./assets/squid.txt");
-        let squid_prototype = parse_prototype(squid_model)?;
-
-        let crab_model = include_str!("./assets/crab.txt");
-        let crab_prototype = parse_prototype(crab_model)?;
-
-        let octopus_model = include_str!("./assets/octopus.txt");
-        let octopus_prototype = parse_prototype(octopus_model)?;
-
-        let rows = [
-            &squid_prototype,
-            &crab_prototype,
-            &crab_prototype,
-            &octopus_prototype,
-            &octopus_prototype,
-        ]
-        .iter()
-        .enumerate()
-        .map(|(idx, &prototype)| {
-            let row = generate_row_of_aliens(
-                prototype,
-                Aliens::INITIAL_X,
-                Aliens::INITIAL_Y + (idx * step) as u16,
-                Aliens::NUMBER,
-                Aliens::STEP,
-            );
+    const MARCH_ROOM: u16 = 3;
+    const WAVE_Y_SHIFT: u16 = 2;
+    /// Free rows kept between a new formation and the bunkers.
+    const WAVE_ROOM: u16 = 6;
+    const SPEED_TABLE_COUNT: usize = 55;
+
+    fn init(
+        scheduler: &Scheduler,
+        viewport: &Viewport,
+        assets: &Assets,
+        config: &AliensConfig,
+        wave: u32,
+    ) -> Aliens {
+        let formation = &assets.formation;
+        let number = Self::get_number_per_row(viewport, formation);
+        let initial_y = Self::get_initial_y(viewport, assets, wave);
+        let slower_than_cycle = Self::get_slower_than_cycle(config, wave);
+        let rows_number = formation.rows.len();
+
+        let rows = formation
+            .rows
+            .iter()
+            .enumerate()
+            .map(|(idx, &species)| {
+                let row = generate_row_of_aliens(
+                    assets.get_species_sprite(species),
+                    formation.offset_x,
+                    initial_y + idx as u16 * formation.row_spacing,
+                    number,
+                    formation.spacing,
+                );
 
-            AliensRow {
-                aliens: row,
-                last_update: SystemTime::now()
-                    + Duration::from_millis(
-                        Aliens::ROWS_DELAY_SHIFT
-                            * (Self::ROWS_NUMBER - 1 - idx) as u64
-                            * MS_PER_UPDATE as u64,
+                AliensRow {
+                    aliens: row,
+                    points: config.get_points(species),
+                    timer: scheduler.delayed_timer(
+                        slower_than_cycle,
+                        formation.stagger * (rows_number - 1 - idx) as u64,
                     ),
-            }
-        })
-        .collect();
+                }
+            })
+            .collect();
 
-        Ok(Aliens {
+        Aliens {
+            initial_count: rows_number * number as usize,
             aliens_rows: rows,
-            times_slower_than_cycle: Aliens::SLOWER_THAN_CYCLE,
+            base_slower_than_cycle: slower_than_cycle,
+            times_slower_than_cycle: slower_than_cycle,
             direction: AlienDirection::Right,
             lasers: vec![],
-            last_shot: SystemTime::now(),
-        })
+            shot_timer: scheduler.timer(config.shots_min_interval),
+            stagger: formation.stagger,
+            spacing: formation.spacing,
+            row_spacing: formation.row_spacing,
+            config: config.clone(),
+        }
     }
 
-    fn update(&mut self) {
-        for aliens_row in self.aliens_rows.iter_mut() {
-            match aliens_row.last_update.elapsed() {
-                Ok(elapsed) => {
-                    if elapsed.as_millis() < self.times_slower_than_cycle * MS_PER_UPDATE {
-                        continue;
-                    }
+    /// Every wave spawns lower, but never so low that the formation starts next to the bunkers.
+    fn get_initial_y(viewport: &Viewport, assets: &Assets, wave: u32) -> u16 {
+        let offset_y = assets.formation.offset_y;
+        let lowest = Self::get_lowest_initial_y(viewport, assets);
+        let wave_y = offset_y + (wave.saturating_sub(1) as u16).saturating_mul(Self::WAVE_Y_SHIFT);
 
-                    if aliens_row.need_to_change_direction(self.direction.clone()) {
-                        self.change_direction();
-                        return;
-                    }
+        wave_y.min(lowest).max(offset_y)
+    }
 
-                    let shift = match self.direction {
-                        AlienDirection::Left => -Aliens::X_SHIFT_PER_UPDATE,
-                        AlienDirection::Right => Aliens::X_SHIFT_PER_UPDATE,
-                    };
+    fn get_lowest_initial_y(viewport: &Viewport, assets: &Assets) -> u16 {
+        Bunkers::get_initial_y(viewport)
+            .saturating_sub(Self::get_height(assets).saturating_add(Self::WAVE_ROOM))
+    }
 
-                    aliens_row.shift_aliens(shift, 0);
-                    aliens_row.last_update = SystemTime::now();
-                }
-                Err(_) => continue,
+    fn get_height(assets: &Assets) -> u16 {
+        let formation = &assets.formation;
+        let last_row_height = formation
+            .rows
+            .last()
+            .map(|&species| get_height(&assets.get_species_sprite(species).get_first_frame().cells))
+            .unwrap_or(0);
+
+        (formation.rows.len() as u16).saturating_sub(1) * formation.row_spacing + last_row_height
+    }
+
+    /// Rows the first wave takes from the top of the screen, and how many it may take on the
+    /// smallest supported viewport while leaving room for one step down above the bunkers.
+    pub(crate) fn get_first_wave_bounds(assets: &Assets) -> (u32, u16) {
+        let viewport = Viewport::new(Viewport::MIN_WIDTH, Viewport::MIN_HEIGHT);
+        let bottom = assets.formation.offset_y as u32 + Self::get_height(assets) as u32;
+
+        (
+            bottom,
+            Bunkers::get_initial_y(&viewport).saturating_sub(Self::Y_SHIFT_PER_UPDATE as u16),
+        )
+    }
+
+    fn get_slower_than_cycle(config: &AliensConfig, wave: u32) -> u64 {
+        config
+            .slower_than_cycle
+            .saturating_sub(wave.saturating_sub(1) as u64 * config.wave_speedup)
+            .max(config.min_slower_than_cycle)
+    }
+
+    /// Narrow viewports get fewer aliens per row, so that the formation always has room to march.
+    fn get_number_per_row(viewport: &Viewport, formation: &Formation) -> u16 {
+        (viewport.get_width() / formation.spacing)
+            .saturating_sub(Self::MARCH_ROOM)
+            .clamp(1, formation.columns)
+    }
+
+    /// Moves the formation and its lasers along with the bunkers, without pushing it off the top
+    /// of the viewport, then fits the formation into the new width.
+    fn relayout(&mut self, previous: &Viewport, viewport: &Viewport) {
+        let Some(top) = self.get_cells().map(|p| p.y).min() else {
+            return;
+        };
+
+        let y_shift = (Bunkers::get_initial_y(viewport) as i32
+            - Bunkers::get_initial_y(previous) as i32)
+            .max(viewport.min_y as i32 - top as i32);
+
+        for alien in self.get_aliens_mut() {
+            alien.move_by(0, y_shift);
+        }
+
+        self.reflow(viewport);
+
+        for laser in self.lasers.iter_mut() {
+            let overflow = laser
+                .position
+                .iter()
+                .map(|p| p.x as i32 - viewport.max_x as i32)
+                .max()
+                .unwrap_or(0)
+                .max(0);
+
+            shift_coords(&mut laser.position, -overflow, y_shift);
+        }
+    }
+
+    /// A formation that still fits the width is only moved back inside the viewport. A wider one
+    /// closes its ranks, down to one free column between aliens, and rows that still don't fit
+    /// wrap onto extra rows, pushing the rows below down.
+    fn reflow(&mut self, viewport: &Viewport) {
+        let (Some(left), Some(right)) = (
+            self.get_cells().map(|p| p.x).min(),
+            self.get_cells().map(|p| p.x).max(),
+        ) else {
+            return;
+        };
+
+        if right <= viewport.max_x {
+            return;
+        }
+
+        let room = viewport.max_x + 1 - viewport.min_x;
+
+        if right - left < room {
+            let x_shift = viewport.max_x as i32 - right as i32;
+
+            for alien in self.get_aliens_mut() {
+                alien.move_by(x_shift, 0);
+            }
+
+            return;
+        }
+
+        let alien_width = self
+            .get_aliens()
+            .map(|a| a.animation.get_sprite().get_width())
+            .max()
+            .unwrap_or(0);
+        let widest_row = self
+            .aliens_rows
+            .iter()
+            .map(|r| r.aliens.len())
+            .max()
+            .unwrap_or(0) as u16;
+        let free_room = room.saturating_sub(alien_width);
+
+        self.spacing = (free_room / widest_row.saturating_sub(1).max(1))
+            .min(self.spacing)
+            .max(alien_width + 1);
+
+        let per_row = (free_room / self.spacing + 1) as usize;
+        let mut pushed_down = 0;
+
+        for aliens_row in std::mem::take(&mut self.aliens_rows) {
+            let Some(y) = aliens_row.aliens.first().map(|a| a.y + pushed_down) else {
+                self.aliens_rows.push(aliens_row);
+                continue;
+            };
+
+            let mut aliens = aliens_row.aliens.into_iter().peekable();
+            let mut line = 0;
+
+            while aliens.peek().is_some() {
+                let line_y = y + line * self.row_spacing;
+                let line_aliens = aliens
+                    .by_ref()
+                    .take(per_row)
+                    .enumerate()
+                    .map(|(column, mut alien)| {
+                        alien.move_to(viewport.min_x + column as u16 * self.spacing, line_y);
+                        alien
+                    })
+                    .collect();
+
+                self.aliens_rows.push(AliensRow {
+                    aliens: line_aliens,
+                    points: aliens_row.points,
+                    timer: aliens_row.timer.clone(),
+                });
+                line += 1;
+            }
+
+            pushed_down += (line - 1) * self.row_spacing;
+        }
+    }
+
+    fn get_aliens(&self) -> impl Iterator<Item = &Alien> {
+        self.aliens_rows.iter().flat_map(|r| &r.aliens)
+    }
+
+    fn get_aliens_mut(&mut self) -> impl Iterator<Item = &mut Alien> {
+        self.aliens_rows
+            .iter_mut()
+            .flat_map(|r| r.aliens.iter_mut())
+    }
+
+    fn get_cells(&self) -> impl Iterator<Item = &Coord> {
+        self.get_aliens().flat_map(|a| &a.position)
+    }
+
+    fn update(&mut self, now: Tick, viewport: &Viewport) {
+        for aliens_row in self.aliens_rows.iter_mut() {
+            if !aliens_row.timer.is_ready(now) {
+                continue;
+            }
+
+            if aliens_row.need_to_change_direction(self.direction.clone(), viewport) {
+                self.change_direction(now);
+                return;
+            }
+
+            let shift = match self.direction {
+                AlienDirection::Left => -Aliens::X_SHIFT_PER_UPDATE,
+                AlienDirection::Right => Aliens::X_SHIFT_PER_UPDATE,
             };
+
+            aliens_row.shift_aliens(shift, 0);
+            aliens_row.timer.restart(now);
         }
     }
 
-    fn change_direction(&mut self) {
+    fn change_direction(&mut self, now: Tick) {
         self.direction = match self.direction {
             AlienDirection::Left => AlienDirection::Right,
             AlienDirection::Right => AlienDirection::Left,
         };
 
-        self.aliens_rows.retain(|r| r.aliens.len() > 0);
+        self.aliens_rows.retain(|r| !r.aliens.is_empty());
         let rows_left = self.aliens_rows.len();
 
         self.aliens_rows
             .iter_mut()
             .enumerate()
-            .filter(|(_, r)| r.aliens.len() > 0)
+            .filter(|(_, r)| !r.aliens.is_empty())
             .for_each(|(idx, r)| {
                 r.shift_aliens(0, Aliens::Y_SHIFT_PER_UPDATE);
-                r.last_update = SystemTime::now()
-                    + Duration::from_millis(
-                        Self::ROWS_DELAY_SHIFT
-                            * (rows_left - 1 - idx) as u64
-                            * MS_PER_UPDATE as u64,
-                    );
+                r.timer
+                    .restart_delayed(now, self.stagger * (rows_left - 1 - idx) as u64);
             });
     }
 
-    fn update_existing_aliens_lasers(&mut self) {
+    fn update_existing_aliens_lasers(&mut self, now: Tick, viewport: &Viewport) {
         for laser in self.lasers.iter_mut() {
-            if laser.last_update.elapsed().unwrap().as_millis()
-                < laser.times_slower_than_cycle * MS_PER_UPDATE
-            {
+            if !laser.timer.fire(now) {
                 continue;
             }
 
-            laser.last_update = SystemTime::now();
-
             laser.position.iter_mut().for_each(|p| {
-                p.y = p.y + Aliens::LASER_SPEED;
+                p.y += self.config.laser_speed;
             });
         }
 
         self.lasers
-            .retain(|l| l.position.iter().find(|p| p.y < VIEWPORT_MAX_Y).is_some());
+            .retain(|l| l.position.iter().any(|p| p.y < viewport.max_y));
     }
 
-    fn shoot(&mut self, player: &Vec<Coord>, laser_prototype: &Vec<Coord>) {
-        if self.last_shot.elapsed().unwrap().as_millis() <= Self::SHOTS_MIN_INTERVAL * MS_PER_UPDATE
-        {
+    fn shoot(
+        &mut self,
+        player: &[Coord],
+        laser_prototype: &[Coord],
+        rng: &mut ChaCha8Rng,
+        now: Tick,
+    ) {
+        if !self.shot_timer.is_ready(now) {
             return;
         }
 
-        let mut rng = rand::thread_rng();
-
         let mut closest_aliens = self
             .aliens_rows
             .iter()
@@ -488,8 +1047,10 @@ impl Aliens {
 
         closest_aliens.sort_by(|(_, d1), (_, d2)| d1.partial_cmp(d2).unwrap());
 
-        for (position, _) in closest_aliens.iter().take(Aliens::MAX_LASERS_AT_A_TIME) {
-            if self.lasers.len() >= Aliens::MAX_LASERS_AT_A_TIME {
+        let max_lasers = self.config.max_lasers_at_a_time;
+
+        for (position, _) in closest_aliens.iter().take(max_lasers) {
+            if self.lasers.len() >= max_lasers {
                 return;
             }
 
@@ -502,57 +1063,61 @@ impl Aliens {
 
                 self.lasers.push(Laser {
                     position,
-                    last_update: SystemTime::now(),
-                    times_slower_than_cycle: Aliens::LASER_SLOWER_THAN_CYCLE,
+                    timer: Timer::new(self.config.laser_slower_than_cycle, now),
                 });
 
-                self.last_shot = SystemTime::now();
+                self.shot_timer.restart(now);
                 return;
             }
         }
     }
 
+    /// Speeds the formation up as it thins out. The thresholds are for a full 5 by 11 formation
+    /// and scale with the number of aliens the wave started with, the last alien is always fastest.
     fn set_speed(&mut self) {
-        self.times_slower_than_cycle = match self.get_aliens_count() {
-            c if c >= 50 => Self::SLOWER_THAN_CYCLE,
-            c if c < 50 && c >= 40 => Self::SLOWER_THAN_CYCLE / 2,
-            c if c < 40 && c >= 30 => Self::SLOWER_THAN_CYCLE / 4,
-            c if c < 30 && c >= 20 => Self::SLOWER_THAN_CYCLE / 8,
-            c if c < 20 && c >= 10 => Self::SLOWER_THAN_CYCLE / 16,
-            c if c < 10 && c >= 5 => Self::SLOWER_THAN_CYCLE / 32,
-            c if c < 5 && c >= 2 => Self::SLOWER_THAN_CYCLE / 50,
+        let base = self.base_slower_than_cycle;
+        let count = self.get_aliens_count();
+        let scaled_count = count * Self::SPEED_TABLE_COUNT / self.initial_count.max(1);
+
+        self.times_slower_than_cycle = match scaled_count {
+            _ if count <= 1 => 1,
+            c if c >= 50 => base,
+            40..=49 => base / 2,
+            30..=39 => base / 4,
+            20..=29 => base / 8,
+            10..=19 => base / 16,
+            5..=9 => base / 32,
+            2..=4 => base / 50,
             _ => 1,
-        };
+        }
+        .max(1);
+
+        for aliens_row in self.aliens_rows.iter_mut() {
+            aliens_row.timer.set_period(self.times_slower_than_cycle);
+        }
     }
 
     fn get_aliens_count(&self) -> usize {
-        self.aliens_rows
-            .iter()
-            .flat_map(|r| &r.aliens)
-            .collect::<Vec<_>>()
-            .len()
+        self.aliens_rows.iter().map(|r| r.aliens.len()).sum()
     }
 
-    fn invaded(&self) -> bool {
+    fn invaded(&self, bunkers_y: u16) -> bool {
         self.aliens_rows
             .iter()
             .flat_map(|r| r.aliens.iter().flat_map(|a| &a.position))
-            .find(|p| p.y >= Bunkers::INITIAL_Y)
-            .is_some()
+            .any(|p| p.y >= bunkers_y)
     }
 }
 
 impl AliensRow {
+    /// Moves the row and advances each alien's animation by one step.
     fn shift_aliens(&mut self, x_shift: i16, y_shift: i16) {
         for alien in self.aliens.iter_mut() {
-            alien.position.iter_mut().for_each(|p| {
-                p.x = (p.x as i16 + x_shift) as u16;
-                p.y = (p.y as i16 + y_shift) as u16;
-            });
+            alien.shift_by(x_shift, y_shift);
         }
     }
 
-    fn need_to_change_direction(&self, direction: AlienDirection) -> bool {
+    fn need_to_change_direction(&self, direction: AlienDirection, viewport: &Viewport) -> bool {
         match self.aliens.len() {
             0 => false,
             _ => {
@@ -560,13 +1125,13 @@ impl AliensRow {
                     AlienDirection::Left => {
                         let alien_in_question = self.aliens.first().unwrap();
                         alien_in_question.position.iter().any(|p| {
-                            p.x as i16 - Aliens::X_SHIFT_PER_UPDATE <= VIEWPORT_MIN_X as i16
+                            p.x as i16 - Aliens::X_SHIFT_PER_UPDATE <= viewport.min_x as i16
                         })
                     }
                     AlienDirection::Right => {
                         let alien_in_question = self.aliens.last().unwrap();
                         alien_in_question.position.iter().any(|p| {
-                            p.x as i16 + Aliens::X_SHIFT_PER_UPDATE > VIEWPORT_MAX_X as i16
+                            p.x as i16 + Aliens::X_SHIFT_PER_UPDATE > viewport.max_x as i16
                         })
                     }
                 }
@@ -575,99 +1140,243 @@ impl AliensRow {
     }
 }
 
+impl Style {
+    pub const PLAIN: Style = Style {
+        foreground: None,
+        background: None,
+        attributes: Attributes::NONE,
+    };
+
+    pub fn with_foreground(foreground: Option<RgbColor>) -> Style {
+        Style {
+            foreground,
+            ..Style::default()
+        }
+    }
+
+    /// Uses `color` as the foreground when the style doesn't set one.
+    pub fn or_foreground(self, color: Option<RgbColor>) -> Style {
+        Style {
+            foreground: self.foreground.or(color),
+            ..self
+        }
+    }
+}
+
+impl Attributes {
+    pub const NONE: Attributes = Attributes {
+        bold: false,
+        dim: false,
+        italic: false,
+        underlined: false,
+        reversed: false,
+    };
+}
+
+impl Alien {
+    fn shift_by(&mut self, x_shift: i16, y_shift: i16) {
+        self.x = (self.x as i16 + x_shift) as u16;
+        self.y = (self.y as i16 + y_shift) as u16;
+
+        if self.animation.advance() {
+            self.position = shift_prototype(&self.animation.get_frame().cells, self.x, self.y);
+            return;
+        }
+
+        shift_coords(&mut self.position, x_shift as i32, y_shift as i32);
+    }
+
+    /// Moves the alien without advancing its animation. Callers keep it inside the viewport.
+    fn move_by(&mut self, x_shift: i32, y_shift: i32) {
+        self.move_to(
+            (self.x as i32 + x_shift) as u16,
+            (self.y as i32 + y_shift) as u16,
+        );
+    }
+
+    fn move_to(&mut self, x: u16, y: u16) {
+        shift_coords(
+            &mut self.position,
+            x as i32 - self.x as i32,
+            y as i32 - self.y as i32,
+        );
+
+        self.x = x;
+        self.y = y;
+    }
+}
+
+impl Ufo {
+    const BONUS_DISPLAY_TICKS: u64 = 100;
+
+    fn get_spawn_delay(rng: &mut ChaCha8Rng, config: &UfoConfig) -> u64 {
+        rng.gen_range(config.min_spawn_delay..=config.max_spawn_delay)
+    }
+
+    /// Enters at a random side of the top row and flies to the opposite one.
+    fn spawn(
+        prototype: &[Coord],
+        viewport: &Viewport,
+        rng: &mut ChaCha8Rng,
+        slower_than_cycle: u64,
+        now: Tick,
+    ) -> Ufo {
+        let (direction, x) = match rng.gen_bool(0.5) {
+            true => (AlienDirection::Right, viewport.min_x),
+            false => (
+                AlienDirection::Left,
+                (viewport.max_x + 1).saturating_sub(get_width(prototype)),
+            ),
+        };
+
+        Ufo {
+            position: shift_prototype(prototype, x, viewport.min_y),
+            direction,
+            timer: Timer::new(slower_than_cycle, now),
+        }
+    }
+
+    /// Moves one cell on and returns whether the UFO is still on screen.
+    fn fly(&mut self, viewport: &Viewport) -> bool {
+        let x_shift = match self.direction {
+            AlienDirection::Left => -1,
+            AlienDirection::Right => 1,
+        };
+
+        let leaves = self.position.iter().any(|p| match self.direction {
+            AlienDirection::Left => p.x <= viewport.min_x,
+            AlienDirection::Right => p.x >= viewport.max_x,
+        });
+
+        if leaves {
+            return false;
+        }
+
+        shift_coords(&mut self.position, x_shift, 0);
+        true
+    }
+}
+
 impl Laser {
     const MODEL_HEIGHT: u16 = 2;
 }
 
 impl Bunkers {
-    const INITIAL_X: u16 = VIEWPORT_MAX_X / (2 * Self::NUMBER);
-    const INITIAL_Y: u16 = 3 * VIEWPORT_MAX_Y / 4;
-    const NUMBER: u16 = 4;
-    const STEP: u16 = VIEWPORT_MAX_X / Self::NUMBER;
+    /// Bunkers are centered within equal slices of the viewport.
+    fn get_initial_x(viewport: &Viewport, width: u16, number: u16) -> u16 {
+        viewport.min_x + (Self::get_step(viewport, number).saturating_sub(width)) / 2
+    }
+
+    fn get_initial_y(viewport: &Viewport) -> u16 {
+        3 * viewport.max_y / 4
+    }
+
+    fn get_step(viewport: &Viewport, number: u16) -> u16 {
+        (viewport.max_x + 1 - viewport.min_x) / number
+    }
+
+    /// Narrow viewports get fewer bunkers, so that they never overlap.
+    fn get_number(viewport: &Viewport, width: u16, number: u16) -> u16 {
+        ((viewport.max_x + 1 - viewport.min_x) / width.max(1)).clamp(1, number.max(1))
+    }
 
-    fn init() -> LigmaResult<Bunkers> {
-        let bunker_model = include_str!("./assets/bunker.txt");
-        let bunker_prototype = parse_prototype(bunker_model)?;
+    fn init(viewport: &Viewport, assets: &Assets, number: u16) -> Bunkers {
+        let width = get_width(&assets.bunker);
+        let number = Self::get_number(viewport, width, number);
 
-        Ok(Bunkers {
+        Bunkers {
             positions: generate_row_of_aliens(
-                &bunker_prototype,
-                Self::INITIAL_X,
-                Self::INITIAL_Y,
-                Self::NUMBER,
-                Self::STEP,
+                assets.bunker.as_slice(),
+                Self::get_initial_x(viewport, width, number),
+                Self::get_initial_y(viewport),
+                number,
+                Self::get_step(viewport, number),
             ),
-        })
+            width,
+            number,
+        }
+    }
+
+    /// Keeps every bunker centered in its slice. Bunkers that no longer fit are dropped.
+    fn relayout(&mut self, previous: &Viewport, viewport: &Viewport) {
+        let y_shift = Self::get_initial_y(viewport) as i32 - Self::get_initial_y(previous) as i32;
+        let number = Self::get_number(viewport, self.width, self.number);
+
+        self.positions.truncate(number as usize);
+
+        for (idx, bunker) in self.positions.iter_mut().enumerate() {
+            let previous_x = Self::get_initial_x(previous, self.width, self.number)
+                + idx as u16 * Self::get_step(previous, self.number);
+            let x = Self::get_initial_x(viewport, self.width, number)
+                + idx as u16 * Self::get_step(viewport, number);
+
+            shift_coords(&mut bunker.position, x as i32 - previous_x as i32, y_shift);
+        }
+
+        self.number = number;
     }
 }
 
 impl Bunker {
-    fn remove_shot_positions(&mut self, laser: &Vec<Coord>) -> bool {
+    /// Knocks a chunk out of the bunker where the laser hits it and returns the hit cell.
+    fn remove_shot_positions(&mut self, laser: &[Coord]) -> Option<Coord> {
         let shot_position = self
             .position
             .iter()
             .find(|p| collides_with_laser(laser, p))
             .cloned();
 
-        match shot_position {
-            Some(shot_position) => {
-                self.position.retain(|p| {
-                    !((p.x == shot_position.x
-                        || p.x == shot_position.x - 1
-                        || p.x == shot_position.x + 1)
-                        && p.y == shot_position.y)
-                });
+        let shot_position = shot_position?;
 
-                true
-            }
-            None => false,
-        }
+        self.position.retain(|p| {
+            !((p.x == shot_position.x || p.x == shot_position.x - 1 || p.x == shot_position.x + 1)
+                && p.y == shot_position.y)
+        });
+
+        Some(shot_position)
     }
 }
 
 fn generate_row_of_aliens<T: Row>(
-    alien_prototype: &Vec<Coord>,
+    prototype: &T::Prototype,
     init_x: u16,
     init_y: u16,
     number: u16,
     step: u16,
 ) -> Vec<T> {
     (0..number)
-        .map(|i| T::generate(shift_prototype(alien_prototype, init_x + i * step, init_y)))
+        .map(|i| T::generate(prototype, init_x + i * step, init_y))
         .collect()
 }
 
-fn parse_prototype(content: &str) -> LigmaResult<Vec<Coord>> {
-    let mut buffer = vec![];
-
-    for line in content.lines() {
-        let mut parts = line.split_whitespace();
-        let x = u16::from_str_radix(parts.next().ok_or(ASSETS_PARSING_ERROR)?, 10)
-            .map_err(|_| U16_CONVERT_ERROR)?;
-        let y = u16::from_str_radix(parts.next().ok_or(ASSETS_PARSING_ERROR)?, 10)
-            .map_err(|_| U16_CONVERT_ERROR)?;
-        let chars = parts.next().unwrap_or(" ").chars().collect::<Vec<char>>();
-
-        let ch = chars.get(0).ok_or(ASSETS_CHAR_ERROR)?;
-
-        buffer.push(Coord { x, y, ch: *ch })
-    }
-
-    Ok(buffer)
-}
-
-fn shift_prototype(prototype: &Vec<Coord>, x_shift: u16, y_shift: u16) -> Vec<Coord> {
+pub(crate) fn shift_prototype(prototype: &[Coord], x_shift: u16, y_shift: u16) -> Vec<Coord> {
     prototype
         .iter()
         .map(|c| Coord {
             x: c.x + x_shift,
             y: c.y + y_shift,
             ch: c.ch,
+            style: c.style,
         })
         .collect()
 }
 
-fn collides_with_laser(laser: &Vec<Coord>, coord: &Coord) -> bool {
+fn get_width(prototype: &[Coord]) -> u16 {
+    prototype.iter().map(|c| c.x + 1).max().unwrap_or(0)
+}
+
+fn get_height(prototype: &[Coord]) -> u16 {
+    prototype.iter().map(|c| c.y + 1).max().unwrap_or(0)
+}
+
+fn shift_coords(coords: &mut [Coord], x_shift: i32, y_shift: i32) {
+    coords.iter_mut().for_each(|c| {
+        c.x = (c.x as i32 + x_shift).max(0) as u16;
+        c.y = (c.y as i32 + y_shift).max(0) as u16;
+    });
+}
+
+fn collides_with_laser(laser: &[Coord], coord: &Coord) -> bool {
     coord.x == laser[0].x && coord.y == laser[0].y || coord.x == laser[1].x && coord.y == laser[1].y
 }
 
@@ -677,3 +1386,85 @@ fn get_distance_between_positions(first: &Coord, second: &Coord) -> f32 {
             + f32::powi(first.y as f32 - second.y as f32, 2),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_alien_positions(state: &State) -> Vec<Vec<Coord>> {
+        state
+            .aliens
+            .aliens_rows
+            .iter()
+            .flat_map(|r| &r.aliens)
+            .map(|a| a.position.clone())
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_and_inputs_play_out_the_same() {
+        let assets = Assets::embedded().unwrap();
+        let viewport = Viewport::new(160, 50);
+        let mut first = State::with_seed(&assets, viewport, 42);
+        let mut second = State::with_seed(&assets, viewport, 42);
+
+        for tick in 0..5000 {
+            let input = Input {
+                left: tick % 400 < 150,
+                right: tick % 400 > 250,
+                fire: tick % 3 == 0,
+            };
+
+            first.step(&input);
+            second.step(&input);
+        }
+
+        assert_eq!(first.get_tick(), second.get_tick());
+        assert_eq!(first.get_score(), second.get_score());
+        assert_eq!(first.get_lives(), second.get_lives());
+        assert_eq!(get_alien_positions(&first), get_alien_positions(&second));
+        assert!(first.get_score() > 0);
+    }
+
+    #[test]
+    fn shrinking_the_viewport_reflows_the_formation() {
+        let assets = Assets::embedded().unwrap();
+        let mut state = State::with_seed(&assets, Viewport::new(160, 50), 42);
+        let viewport = Viewport::new(Viewport::MIN_WIDTH, Viewport::MIN_HEIGHT);
+        let count = get_alien_positions(&state).len();
+
+        state.resize(viewport);
+
+        let positions = get_alien_positions(&state);
+        let mut cells = vec![];
+
+        for position in positions.iter() {
+            let mut alien_cells: Vec<(u16, u16)> = position.iter().map(|c| (c.x, c.y)).collect();
+            alien_cells.sort_unstable();
+            alien_cells.dedup();
+            cells.extend(alien_cells);
+        }
+
+        let cells_count = cells.len();
+        cells.sort_unstable();
+        cells.dedup();
+
+        assert_eq!(positions.len(), count);
+        assert_eq!(cells.len(), cells_count);
+        assert!(cells
+            .iter()
+            .all(|&(x, _)| (viewport.min_x..=viewport.max_x).contains(&x)));
+    }
+
+    #[test]
+    fn state_can_move_to_another_thread() {
+        let assets = Assets::embedded().unwrap();
+        let state = State::with_seed(&assets, Viewport::new(160, 50), 42);
+
+        let score = std::thread::spawn(move || state.get_score())
+            .join()
+            .unwrap();
+
+        assert_eq!(score, 0);
+    }
+}
@@ -0,0 +1,51 @@
+/// Playfield bounds in terminal cells. Everything is laid out relative to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Viewport {
+    pub min_x: u16,
+    pub max_x: u16,
+    pub min_y: u16,
+    pub max_y: u16,
+}
+
+impl Viewport {
+    pub const MIN_WIDTH: u16 = 90;
+    pub const MIN_HEIGHT: u16 = 44;
+    const DEFAULT_WIDTH: u16 = 201;
+    const DEFAULT_HEIGHT: u16 = 73;
+
+    pub fn new(width: u16, height: u16) -> Viewport {
+        Viewport {
+            min_x: 1,
+            max_x: width.saturating_sub(1).max(1),
+            min_y: 1,
+            max_y: height.saturating_sub(1).max(1),
+        }
+    }
+
+    /// Viewport of the current terminal, falling back to the default size when it can't be
+    /// queried.
+    pub fn from_terminal() -> Viewport {
+        match crossterm::terminal::size() {
+            Ok((width, height)) => Viewport::new(width, height),
+            Err(_) => Viewport::default(),
+        }
+    }
+
+    pub fn get_width(&self) -> u16 {
+        self.max_x + 1
+    }
+
+    pub fn get_height(&self) -> u16 {
+        self.max_y + 1
+    }
+
+    pub fn fits(&self) -> bool {
+        self.get_width() >= Self::MIN_WIDTH && self.get_height() >= Self::MIN_HEIGHT
+    }
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Viewport::new(Self::DEFAULT_WIDTH, Self::DEFAULT_HEIGHT)
+    }
+}